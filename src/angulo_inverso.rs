@@ -0,0 +1,126 @@
+/// Archivo para buscar el angulo que da una longitud de sombra pedida
+use crate::barrido::EscenaOrdenada;
+#[cfg(test)]
+use crate::pruebas::flatlanders_del_ejemplo;
+
+/// Cantidad maxima de pasos de biseccion, sobra para llegar a la precision de f64
+const MAX_ITERACIONES: usize = 200;
+
+/// Resultado de buscar el angulo para una longitud
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResultadoInverso {
+    /// Todos los angulos entre desde y hasta dan la longitud pedida dentro de la tolerancia
+    ///
+    /// Como la sombra baja con el angulo, hay al menos la longitud pedida para los angulos hasta `hasta`
+    Angulos {
+        /// Menor angulo con la longitud pedida
+        desde: f64,
+        /// Mayor angulo con la longitud pedida
+        hasta: f64,
+    },
+    /// La longitud pedida no se alcanza en el rango
+    Inalcanzable {
+        /// Longitud en el angulo mas alto del rango
+        minima: f64,
+        /// Longitud en el angulo mas bajo del rango
+        maxima: f64,
+    },
+}
+
+/// Busca los angulos entre desde y hasta donde la longitud de la union es objetivo, con la tolerancia dada
+///
+/// Con 0 < angulo <= 90 la longitud nunca sube cuando sube el angulo, asi que se biseca
+/// buscando el primer angulo que no se pasa del objetivo y el ultimo que llega
+///
+/// Devuelve None si el rango no cumple 0 < desde <= hasta <= 90 o la tolerancia es negativa
+pub fn angulo_para_longitud(
+    escena: &EscenaOrdenada,
+    objetivo: f64,
+    desde: f64,
+    hasta: f64,
+    tolerancia: f64,
+) -> Option<ResultadoInverso> {
+    let rango_valido = desde > 0.0 && desde <= hasta && hasta <= 90.0 && tolerancia >= 0.0;
+    if !rango_valido {
+        return None;
+    }
+    let maxima = escena.longitud_en_angulo(desde);
+    let minima = escena.longitud_en_angulo(hasta);
+    if maxima < objetivo - tolerancia || minima > objetivo + tolerancia {
+        return Some(ResultadoInverso::Inalcanzable { minima, maxima });
+    }
+
+    let primero = biseccionar(desde, hasta, |angulo| {
+        escena.longitud_en_angulo(angulo) <= objetivo + tolerancia
+    });
+    let ultimo = biseccionar(desde, hasta, |angulo| {
+        escena.longitud_en_angulo(angulo) < objetivo - tolerancia
+    });
+    Some(ResultadoInverso::Angulos {
+        desde: primero,
+        hasta: ultimo.min(hasta).max(primero),
+    })
+}
+
+/// Devuelve el primer angulo del rango donde se cumple la condicion, que tiene que ser monotona
+///
+/// Si no se cumple en ningun lado devuelve hasta
+fn biseccionar(desde: f64, hasta: f64, condicion: impl Fn(f64) -> bool) -> f64 {
+    if condicion(desde) {
+        return desde;
+    }
+    let mut bajo = desde;
+    let mut alto = hasta;
+    for _ in 0..MAX_ITERACIONES {
+        let medio = bajo + (alto - bajo) / 2.0;
+        if medio <= bajo || medio >= alto {
+            break;
+        }
+        if condicion(medio) {
+            alto = medio;
+        } else {
+            bajo = medio;
+        }
+    }
+    alto
+}
+
+#[test]
+fn encuentra_el_angulo_del_ejemplo_de_30_grados() {
+    let escena = EscenaOrdenada::nueva(flatlanders_del_ejemplo());
+    let resultado = angulo_para_longitud(&escena, 446.4101615137755, 10.0, 80.0, 1e-9).unwrap();
+    match resultado {
+        ResultadoInverso::Angulos { desde, hasta } => {
+            assert!((desde - 30.0).abs() < 1e-6);
+            assert!((hasta - 30.0).abs() < 1e-6);
+        }
+        ResultadoInverso::Inalcanzable { .. } => panic!("tenia que encontrarlo"),
+    }
+}
+
+#[test]
+fn longitud_muy_grande_es_inalcanzable() {
+    let escena = EscenaOrdenada::nueva(flatlanders_del_ejemplo());
+    let resultado = angulo_para_longitud(&escena, 1e7, 10.0, 80.0, 1e-4).unwrap();
+    assert!(matches!(resultado, ResultadoInverso::Inalcanzable { .. }));
+}
+
+#[test]
+fn tolerancia_grande_da_un_rango_de_angulos() {
+    let escena = EscenaOrdenada::nueva(flatlanders_del_ejemplo());
+    let objetivo = escena.longitud_en_angulo(45.0);
+    let resultado = angulo_para_longitud(&escena, objetivo, 10.0, 80.0, 10.0).unwrap();
+    let ResultadoInverso::Angulos { desde, hasta } = resultado else {
+        panic!("tenia que encontrarlo");
+    };
+    assert!(desde < 45.0 && 45.0 < hasta);
+    assert!(escena.longitud_en_angulo(desde) <= objetivo + 10.0 + 1e-9);
+    assert!(escena.longitud_en_angulo(hasta) >= objetivo - 10.0 - 1e-9);
+}
+
+#[test]
+fn rango_invalido_devuelve_none() {
+    let escena = EscenaOrdenada::nueva(flatlanders_del_ejemplo());
+    assert!(angulo_para_longitud(&escena, 100.0, 10.0, 100.0, 1e-4).is_none());
+    assert!(angulo_para_longitud(&escena, 100.0, 50.0, 10.0, 1e-4).is_none());
+}
@@ -0,0 +1,156 @@
+/// Archivo para animar el barrido del sol: un SVG animado con SMIL o un SVG por cuadro
+use crate::barrido::rango_de_sombras;
+use crate::dibujo_svg::{OpcionesDeSvg, escena_a_svg, escribir_escena};
+use crate::flatlander::Flatlander;
+use crate::longitud_union::{sombras_desde_flatlanders, union_de_intervalos};
+#[cfg(test)]
+use crate::pruebas::dos_flatlanders;
+use std::fmt::Write;
+
+/// Segundos que se muestra cada angulo si no se pide otra cosa
+pub const SEGUNDOS_POR_CUADRO: f64 = 0.1;
+
+/// Funcion interna que fija el rango en las opciones, para que los cuadros no salten de escala
+fn opciones_del_barrido(
+    flatlanders: &[Flatlander],
+    angulos: &[f64],
+    opciones: &OpcionesDeSvg,
+) -> OpcionesDeSvg {
+    OpcionesDeSvg {
+        rango: opciones
+            .rango
+            .or_else(|| Some(rango_de_sombras(flatlanders, angulos))),
+        ..*opciones
+    }
+}
+
+/// Dibuja un SVG entero por cada angulo, todos con la misma escala
+///
+/// Cada cuadro tiene al pie el angulo y el total de la union en ese angulo, asi se ve como las
+/// sombras se acortan y las regiones unidas se separan a medida que sube el sol
+pub fn cuadros_del_barrido(
+    flatlanders: &[Flatlander],
+    angulos: &[f64],
+    opciones: &OpcionesDeSvg,
+) -> Vec<String> {
+    let opciones = opciones_del_barrido(flatlanders, angulos, opciones);
+    angulos
+        .iter()
+        .map(|&angulo| {
+            let union = union_de_intervalos(sombras_desde_flatlanders(flatlanders, angulo));
+            escena_a_svg(flatlanders, angulo, &union, &opciones)
+        })
+        .collect()
+}
+
+/// Dibuja el barrido en un solo SVG que pasa por los angulos en orden y vuelve a empezar
+///
+/// Cada angulo es un grupo oculto que una animacion SMIL muestra durante su parte del ciclo. El
+/// primer cuadro queda visible de entrada, asi los visores sin SMIL muestran al menos ese
+pub fn barrido_a_svg_animado(
+    flatlanders: &[Flatlander],
+    angulos: &[f64],
+    segundos_por_cuadro: f64,
+    opciones: &OpcionesDeSvg,
+) -> String {
+    let opciones = opciones_del_barrido(flatlanders, angulos, opciones);
+    let cuadros = angulos.len();
+    let duracion = segundos_por_cuadro * cuadros as f64;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        opciones.ancho, opciones.alto
+    );
+    let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>");
+    for (i, &angulo) in angulos.iter().enumerate() {
+        let visible = if i == 0 { "visible" } else { "hidden" };
+        let _ = writeln!(svg, "<g visibility=\"{visible}\">");
+        let _ = writeln!(
+            svg,
+            "<animate attributeName=\"visibility\" values=\"hidden;visible;hidden\" keyTimes=\"0;{:.6};{:.6}\" dur=\"{duracion}s\" calcMode=\"discrete\" repeatCount=\"indefinite\"/>",
+            i as f64 / cuadros as f64,
+            (i + 1) as f64 / cuadros as f64
+        );
+        let union = union_de_intervalos(sombras_desde_flatlanders(flatlanders, angulo));
+        escribir_escena(&mut svg, flatlanders, angulo, &union, &opciones);
+        let _ = writeln!(svg, "</g>");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Nombre del archivo de un cuadro, con ceros adelante para que se ordenen bien
+///
+/// Todos los nombres tienen el mismo ancho, por ejemplo cuadro_007.svg si hay cientos de cuadros,
+/// que es lo que esperan las herramientas que arman videos con una secuencia numerada
+pub fn nombre_de_cuadro(indice: usize, cuadros: usize) -> String {
+    let cifras = cuadros.to_string().len().max(3);
+    format!("cuadro_{:0cifras$}.svg", indice + 1)
+}
+
+#[test]
+fn los_cuadros_muestran_el_total_de_cada_angulo() {
+    let cuadros = cuadros_del_barrido(
+        &dos_flatlanders(3.0),
+        &[45.0, 90.0],
+        &OpcionesDeSvg::default(),
+    );
+    assert_eq!(cuadros.len(), 2);
+    // a 45 las sombras [0, 2] y [3, 5] no se tocan, a 90 no hay sombra
+    assert!(cuadros[0].contains("angulo 45 total 4.0000</text>"));
+    assert!(cuadros[1].contains("angulo 90 total 0.0000</text>"));
+}
+
+#[test]
+fn todos_los_cuadros_usan_la_misma_escala() {
+    let cuadros = cuadros_del_barrido(
+        &dos_flatlanders(3.0),
+        &[30.0, 80.0],
+        &OpcionesDeSvg::default(),
+    );
+    // el flatlander de x = 3 queda en el mismo pixel aunque las sombras cambien
+    let pie = |svg: &str| {
+        let linea = svg
+            .lines()
+            .find(|l| l.contains("<title>2 "))
+            .unwrap()
+            .to_string();
+        linea[..linea.find(" y1").unwrap()].to_string()
+    };
+    assert_eq!(pie(&cuadros[0]), pie(&cuadros[1]));
+}
+
+#[test]
+fn las_regiones_unidas_se_separan() {
+    let flatlanders = dos_flatlanders(3.0);
+    let angulos = [20.0, 60.0];
+    let cuadros = cuadros_del_barrido(&flatlanders, &angulos, &OpcionesDeSvg::default());
+    let uniones: Vec<usize> = cuadros
+        .iter()
+        .map(|svg| svg.matches("height=\"4\"").count())
+        .collect();
+    assert_eq!(uniones, vec![1, 2]);
+}
+
+#[test]
+fn svg_animado_con_un_grupo_por_angulo() {
+    let svg = barrido_a_svg_animado(
+        &dos_flatlanders(3.0),
+        &[10.0, 45.0, 80.0],
+        0.5,
+        &OpcionesDeSvg::default(),
+    );
+    assert_eq!(svg.matches("<svg ").count(), 1);
+    assert_eq!(svg.matches("<animate ").count(), 3);
+    assert_eq!(svg.matches("<g visibility=\"visible\">").count(), 1);
+    assert!(svg.contains("keyTimes=\"0;0.333333;0.666667\" dur=\"1.5s\""));
+    assert!(svg.ends_with("</g>\n</svg>\n"));
+}
+
+#[test]
+fn nombres_con_el_mismo_ancho() {
+    assert_eq!(nombre_de_cuadro(0, 71), "cuadro_001.svg");
+    assert_eq!(nombre_de_cuadro(1233, 5000), "cuadro_1234.svg");
+}
@@ -0,0 +1,592 @@
+/// Archivo para leer los argumentos de la linea de comandos sin bibliotecas externas
+use crate::barrido::parsear_rango_de_angulos;
+use crate::diagnostico::FormatoDeDiagnosticos;
+use crate::dibujo_terminal::JuegoDeCaracteres;
+use crate::formatos::{FormatoDeEntrada, FormatoDeSalida};
+use crate::geometria::angulo_de_sol_valido;
+use crate::lote::FormatoDeLote;
+use crate::mapa_de_calor::ModoDeMapa;
+use crate::mensajes::Idioma;
+use crate::numeros::{FormatoDeNumeros, SeparadorDecimal};
+use crate::parseador::{CategoriaDeError, Limites, ModoDeParseo};
+use std::fmt;
+use std::str::FromStr;
+
+/// Codigo de salida cuando los argumentos no sirven
+pub const SALIDA_USO: u8 = 2;
+/// Codigo de salida cuando no se pudo leer la entrada o escribir la salida
+pub const SALIDA_IO: u8 = 3;
+/// Codigo de salida cuando faltan o sobran lineas o valores
+pub const SALIDA_ESTRUCTURA: u8 = 4;
+/// Codigo de salida cuando un valor no es un numero
+pub const SALIDA_NUMERO: u8 = 5;
+/// Codigo de salida cuando un numero esta fuera de los limites
+pub const SALIDA_RANGO: u8 = 6;
+/// Codigo de salida cuando la entrada no respeta su formato
+pub const SALIDA_FORMATO: u8 = 7;
+
+/// Opciones que el lote no usa, porque cada caso se parsea como una escena clasica sola
+const OPCIONES_SIN_LOTE: [&str; 4] = [
+    "--estricto",
+    "--permisivo",
+    "--coma-decimal",
+    "--diagnosticos",
+];
+
+/// Mayor cantidad de decimales que se acepta en --precision, un f64 no tiene tantos con sentido
+pub const PRECISION_MAXIMA: usize = 30;
+
+/// Texto de --help
+pub const AYUDA: &str = "\
+Uso: ejercicio_individual_1_santiagosielecki [OPCIONES] [ENTRADA]
+
+Calcula la longitud total a la sombra de los flatlanders. ENTRADA es la ruta de un archivo,
+o - para la entrada estandar, que es la que se usa si no se da ninguna.
+
+Entrada:
+  --input-format F       clasico, anotado, csv o json; si no se da se adivina
+  --angle A, --angulo A  angulo del sol, le gana al de la entrada
+  --estricto             no acepta datos de mas
+  --permisivo            tolera BOM, lineas en blanco, CRLF y un n que no coincide
+  --coma-decimal         numeros con coma decimal y punto de miles
+  --lote                 varias escenas, la primera linea es la cantidad
+  --lote-continuo        varias escenas hasta el final de la entrada
+
+Limites:
+  --n-min N  --n-max N  --x-min X  --x-max X  --h-min H  --h-max H
+  --angulo-min A  --angulo-max A
+  --ambos-lados          acepta angulos entre 0 y 180, sin incluirlos
+
+Salida:
+  --output RUTA          escribe en el archivo en vez de la salida estandar
+  --output-format F      texto, csv o json
+  --precision P          decimales de los resultados, 13 si no se da
+  --intervalos           regiones a la sombra
+  --huecos               regiones iluminadas entre la primera y la ultima sombra
+  --profundidad          perfil de cobertura y profundidad maxima
+  --atribucion           sombra propia, exclusiva, compartida y reparto de cada flatlander
+  --angulos D:H:P        tabla con el total para cada angulo del rango
+  --objetivo L           angulos donde el total vale L
+  --svg RUTA             ademas dibuja la escena en un archivo SVG
+  --ascii                dibuja la escena como texto en vez de dar el total
+  --unicode              igual que --ascii pero con bloques de Unicode
+  --curva                agrega al dibujo la curva del total segun el angulo
+  --ancho N              columnas del dibujo; si no se da se usa COLUMNS y si no, 80
+  --mapa RUTA            ademas escribe el mapa de sombra angulo por posicion, PPM si la
+                         ruta termina en .ppm y PGM si no
+  --mapa-angulos D:H:P   filas del mapa; si no se da, los limites de angulo cada 0.1
+  --mapa-columnas N      columnas del mapa, 1000 si no se da
+  --mapa-profundidad     el mapa muestra cuantas sombras cubren cada punto
+  --animacion RUTA       ademas escribe un SVG animado del sol barriendo los angulos
+  --cuadros DIRECTORIO   ademas escribe un SVG por angulo, cuadro_001.svg, cuadro_002.svg...
+  --animacion-angulos D:H:P
+                         angulos de la animacion y los cuadros, 10:80:1 si no se da
+  --animacion-segundos S segundos que dura cada angulo en la animacion, 0.1 si no se da
+  --diagnosticos F       en vez de calcular, lista todos los errores de la entrada; texto o json
+
+  --idioma I             es o en; si no se da, los errores y la ayuda siguen a LC_ALL,
+                         LC_MESSAGES o LANG y la salida queda en es
+  -h, --help             muestra esta ayuda
+
+Codigos de salida:
+  0 sin errores, 2 argumentos invalidos, 3 error al leer o escribir, 4 faltan o sobran datos,
+  5 numero invalido, 6 fuera de rango, 7 formato invalido
+";
+
+/// Texto de --help en ingles
+pub const AYUDA_EN_INGLES: &str = "\
+Usage: ejercicio_individual_1_santiagosielecki [OPTIONS] [INPUT]
+
+Computes the total length in shadow of the flatlanders. INPUT is the path of a file,
+or - for standard input, which is used when none is given.
+
+Input:
+  --input-format F       clasico, anotado, csv or json; guessed when not given
+  --angle A, --angulo A  sun angle, overrides the one in the input
+  --estricto             rejects extra data
+  --permisivo            tolerates BOM, blank lines, CRLF and a mismatched n
+  --coma-decimal         numbers with decimal comma and thousands dot
+  --lote                 several scenes, the first line is how many
+  --lote-continuo        several scenes until the end of the input
+
+Limits:
+  --n-min N  --n-max N  --x-min X  --x-max X  --h-min H  --h-max H
+  --angulo-min A  --angulo-max A
+  --ambos-lados          accepts angles strictly between 0 and 180
+
+Output:
+  --output PATH          writes to the file instead of standard output
+  --output-format F      texto, csv or json
+  --precision P          decimals of the results, 13 when not given
+  --intervalos           regions in shadow
+  --huecos               lit regions between the first and the last shadow
+  --profundidad          coverage profile and maximum depth
+  --atribucion           own, exclusive, shared and split shadow of each flatlander
+  --angulos D:H:P        table with the total for each angle in the range
+  --objetivo L           angles where the total equals L
+  --svg PATH             also draws the scene into an SVG file
+  --ascii                draws the scene as text instead of printing the total
+  --unicode              same as --ascii but with Unicode blocks
+  --curva                adds the curve of the total against the angle to the drawing
+  --ancho N              columns of the drawing; when not given COLUMNS is used, and otherwise 80
+  --mapa PATH            also writes the angle by position shade map, PPM if the path
+                         ends in .ppm and PGM otherwise
+  --mapa-angulos D:H:P   rows of the map; when not given, the angle limits every 0.1
+  --mapa-columnas N      columns of the map, 1000 when not given
+  --mapa-profundidad     the map shows how many shadows cover each point
+  --animacion PATH       also writes an animated SVG of the sun sweeping the angles
+  --cuadros DIRECTORY    also writes one SVG per angle, cuadro_001.svg, cuadro_002.svg...
+  --animacion-angulos D:H:P
+                         angles of the animation and the frames, 10:80:1 when not given
+  --animacion-segundos S seconds each angle lasts in the animation, 0.1 when not given
+  --diagnosticos F       instead of computing, lists every error in the input; texto or json
+
+  --idioma I             es or en; when not given, errors and help follow LC_ALL,
+                         LC_MESSAGES or LANG and the output stays in es
+  -h, --help             shows this help
+
+Exit codes:
+  0 no errors, 2 invalid arguments, 3 read or write error, 4 missing or extra data,
+  5 invalid number, 6 out of range, 7 invalid format
+";
+
+/// Devuelve el codigo de salida que corresponde a cada categoria de error de parseo
+pub fn codigo_de_salida(categoria: CategoriaDeError) -> u8 {
+    match categoria {
+        CategoriaDeError::Io => SALIDA_IO,
+        CategoriaDeError::Estructura => SALIDA_ESTRUCTURA,
+        CategoriaDeError::Numero => SALIDA_NUMERO,
+        CategoriaDeError::Rango => SALIDA_RANGO,
+        CategoriaDeError::Formato => SALIDA_FORMATO,
+    }
+}
+
+/// Reportes que se imprimen despues del total
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Reportes {
+    /// Regiones a la sombra
+    pub intervalos: bool,
+    /// Regiones iluminadas entre la primera y la ultima sombra
+    pub huecos: bool,
+    /// Perfil de cobertura y profundidad maxima
+    pub profundidad: bool,
+    /// Reparto de la sombra entre los flatlanders
+    pub atribucion: bool,
+}
+
+/// Todo lo que se puede pedir por argumento, ya validado
+///
+/// Por defecto es lo de siempre: la escena clasica por la entrada estandar y el total con 13 decimales
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Configuracion {
+    /// Ruta de la entrada, None para la entrada estandar
+    pub entrada: Option<String>,
+    /// Ruta de la salida, None para la salida estandar
+    pub salida: Option<String>,
+    /// Angulo que le gana al de la entrada
+    pub angulo: Option<f64>,
+    /// Limites para validar la entrada
+    pub limites: Limites,
+    /// Que tan exigente es el parseo
+    pub modo: ModoDeParseo,
+    /// Decimales y separador de la salida, el separador tambien se usa para la entrada
+    pub numeros: FormatoDeNumeros,
+    /// Formato de la entrada, None para adivinarlo
+    pub formato_de_entrada: Option<FormatoDeEntrada>,
+    /// Formato de la salida
+    pub formato_de_salida: FormatoDeSalida,
+    /// Angulos para imprimir la tabla en vez del total
+    pub angulos: Option<Vec<f64>>,
+    /// Longitud para buscar el angulo que la da
+    pub objetivo: Option<f64>,
+    /// Si la entrada tiene varias escenas y como se sabe cuantas
+    pub lote: Option<FormatoDeLote>,
+    /// Reportes extra
+    pub reportes: Reportes,
+    /// Ruta donde dibujar la escena en SVG
+    pub svg: Option<String>,
+    /// Dibujar la escena como texto con estos caracteres en vez de dar el total
+    pub dibujo: Option<JuegoDeCaracteres>,
+    /// Agregar al dibujo la curva del total segun el angulo
+    pub curva: bool,
+    /// Columnas del dibujo, None para sacarlo de la terminal
+    pub ancho: Option<usize>,
+    /// Ruta donde escribir el mapa de sombra
+    pub mapa: Option<String>,
+    /// Angulos de las filas del mapa, None para recorrer los limites
+    pub mapa_angulos: Option<Vec<f64>>,
+    /// Columnas del mapa, None para las de siempre
+    pub mapa_columnas: Option<usize>,
+    /// Que muestra cada pixel del mapa
+    pub mapa_modo: ModoDeMapa,
+    /// Ruta donde escribir el SVG animado del barrido
+    pub animacion: Option<String>,
+    /// Directorio donde escribir un SVG por cada angulo del barrido
+    pub cuadros: Option<String>,
+    /// Angulos del barrido, None para los de siempre
+    pub animacion_angulos: Option<Vec<f64>>,
+    /// Segundos de cada angulo en la animacion, None para los de siempre
+    pub animacion_segundos: Option<f64>,
+    /// Listar los errores de la entrada en este formato en vez de calcular
+    pub diagnosticos: Option<FormatoDeDiagnosticos>,
+    /// Idioma de los mensajes, None para sacarlo de LANG
+    pub idioma: Option<Idioma>,
+    /// Mostrar la ayuda y no hacer nada mas
+    pub ayuda: bool,
+}
+
+/// Errores de los argumentos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorDeArgumento {
+    /// Falta el valor de la opcion o no sirve
+    ValorInvalido {
+        /// Opcion tal como se escribio
+        opcion: String,
+    },
+    /// La opcion no existe
+    OpcionDesconocida {
+        /// Opcion tal como se escribio
+        opcion: String,
+    },
+    /// Se dio mas de una entrada
+    EntradaRepetida {
+        /// La entrada que sobra
+        entrada: String,
+    },
+    /// La opcion no tiene efecto junto con otra que ya se dio
+    OpcionesIncompatibles {
+        /// Opcion que no se puede usar
+        opcion: String,
+        /// Opcion con la que choca
+        con: String,
+    },
+}
+
+impl fmt::Display for ErrorDeArgumento {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorDeArgumento::ValorInvalido { opcion } => {
+                write!(f, "valor invalido para {opcion}")
+            }
+            ErrorDeArgumento::OpcionDesconocida { opcion } => {
+                write!(f, "opcion desconocida {opcion}")
+            }
+            ErrorDeArgumento::EntradaRepetida { entrada } => {
+                write!(f, "sobra la entrada {entrada}, solo se lee una")
+            }
+            ErrorDeArgumento::OpcionesIncompatibles { opcion, con } => {
+                write!(f, "no se puede usar {opcion} con {con}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ErrorDeArgumento {}
+
+/// Parsea los argumentos, sin el nombre del programa
+///
+/// Las opciones pueden ir en cualquier orden y el valor de una opcion es siempre el argumento
+/// que le sigue, asi --x-min -5 funciona. Lo que no empieza con - es la ruta de la entrada
+pub fn parsear_argumentos(argumentos: &[String]) -> Result<Configuracion, ErrorDeArgumento> {
+    let mut configuracion = Configuracion::default();
+    // --ambos-lados cambia de donde se parte, asi que tiene que estar antes que los limites sueltos
+    if argumentos.iter().any(|a| a == "--ambos-lados") {
+        configuracion.limites = Limites::con_sol_de_ambos_lados();
+    }
+    let mut vio_entrada = false;
+    let mut iterador = argumentos.iter();
+
+    while let Some(argumento) = iterador.next() {
+        let opcion = argumento.as_str();
+        let mut valor = || iterador.next().ok_or_else(|| valor_invalido(opcion));
+        let limites = &mut configuracion.limites;
+        match opcion {
+            "-h" | "--help" => configuracion.ayuda = true,
+            "--intervalos" => configuracion.reportes.intervalos = true,
+            "--huecos" => configuracion.reportes.huecos = true,
+            "--profundidad" => configuracion.reportes.profundidad = true,
+            "--atribucion" => configuracion.reportes.atribucion = true,
+            "--estricto" => configuracion.modo = ModoDeParseo::Estricto,
+            "--permisivo" => configuracion.modo = ModoDeParseo::Permisivo,
+            "--coma-decimal" => configuracion.numeros.separador = SeparadorDecimal::Coma,
+            "--ambos-lados" => {}
+            "--lote" => configuracion.lote = Some(FormatoDeLote::ConCantidad),
+            "--lote-continuo" => configuracion.lote = Some(FormatoDeLote::HastaElFinal),
+            "--angle" | "--angulo" => configuracion.angulo = Some(parsear(valor()?, opcion)?),
+            "--objetivo" => configuracion.objetivo = Some(parsear(valor()?, opcion)?),
+            "--output" => configuracion.salida = Some(valor()?.clone()),
+            "--svg" => configuracion.svg = Some(valor()?.clone()),
+            "--ascii" => configuracion.dibujo = Some(JuegoDeCaracteres::Ascii),
+            "--unicode" => configuracion.dibujo = Some(JuegoDeCaracteres::Unicode),
+            "--curva" => configuracion.curva = true,
+            "--mapa" => configuracion.mapa = Some(valor()?.clone()),
+            "--mapa-profundidad" => configuracion.mapa_modo = ModoDeMapa::Profundidad,
+            "--mapa-angulos" => {
+                let angulos = parsear_rango_de_angulos(valor()?)
+                    .filter(|a| a.iter().all(|&angulo| angulo_de_sol_valido(angulo)))
+                    .ok_or_else(|| valor_invalido(opcion))?;
+                configuracion.mapa_angulos = Some(angulos);
+            }
+            "--mapa-columnas" => {
+                let columnas = parsear(valor()?, opcion)?;
+                if columnas == 0 {
+                    return Err(valor_invalido(opcion));
+                }
+                configuracion.mapa_columnas = Some(columnas);
+            }
+            "--animacion" => configuracion.animacion = Some(valor()?.clone()),
+            "--cuadros" => configuracion.cuadros = Some(valor()?.clone()),
+            "--animacion-angulos" => {
+                let angulos = parsear_rango_de_angulos(valor()?)
+                    .filter(|a| a.iter().all(|&angulo| angulo_de_sol_valido(angulo)))
+                    .ok_or_else(|| valor_invalido(opcion))?;
+                configuracion.animacion_angulos = Some(angulos);
+            }
+            "--animacion-segundos" => {
+                let segundos: f64 = parsear(valor()?, opcion)?;
+                if !(segundos > 0.0 && segundos.is_finite()) {
+                    return Err(valor_invalido(opcion));
+                }
+                configuracion.animacion_segundos = Some(segundos);
+            }
+            "--ancho" => {
+                let ancho = parsear(valor()?, opcion)?;
+                if ancho == 0 {
+                    return Err(valor_invalido(opcion));
+                }
+                configuracion.ancho = Some(ancho);
+            }
+            "--input-format" => configuracion.formato_de_entrada = Some(parsear(valor()?, opcion)?),
+            "--output-format" => configuracion.formato_de_salida = parsear(valor()?, opcion)?,
+            "--diagnosticos" => configuracion.diagnosticos = Some(parsear(valor()?, opcion)?),
+            "--idioma" => configuracion.idioma = Some(parsear(valor()?, opcion)?),
+            "--precision" => {
+                let decimales = parsear(valor()?, opcion)?;
+                if decimales > PRECISION_MAXIMA {
+                    return Err(valor_invalido(opcion));
+                }
+                configuracion.numeros.decimales = decimales;
+            }
+            "--angulos" => {
+                let angulos = parsear_rango_de_angulos(valor()?)
+                    .filter(|a| a.iter().all(|&angulo| angulo_de_sol_valido(angulo)))
+                    .ok_or_else(|| valor_invalido(opcion))?;
+                configuracion.angulos = Some(angulos);
+            }
+            "--n-min" => limites.n_min = parsear(valor()?, opcion)?,
+            "--n-max" => limites.n_max = parsear(valor()?, opcion)?,
+            "--x-min" => limites.x_min = parsear(valor()?, opcion)?,
+            "--x-max" => limites.x_max = parsear(valor()?, opcion)?,
+            "--h-min" => limites.h_min = parsear(valor()?, opcion)?,
+            "--h-max" => limites.h_max = parsear(valor()?, opcion)?,
+            "--angulo-min" => limites.angulo_min = parsear(valor()?, opcion)?,
+            "--angulo-max" => limites.angulo_max = parsear(valor()?, opcion)?,
+            _ if opcion.starts_with('-') && opcion != "-" => {
+                return Err(ErrorDeArgumento::OpcionDesconocida {
+                    opcion: opcion.to_string(),
+                });
+            }
+            _ => {
+                if vio_entrada {
+                    return Err(ErrorDeArgumento::EntradaRepetida {
+                        entrada: opcion.to_string(),
+                    });
+                }
+                vio_entrada = true;
+                configuracion.entrada = (opcion != "-").then(|| opcion.to_string());
+            }
+        }
+    }
+
+    // la curva va en el dibujo, asi que sola lo pide en ASCII
+    if configuracion.curva && configuracion.dibujo.is_none() {
+        configuracion.dibujo = Some(JuegoDeCaracteres::Ascii);
+    }
+
+    // con el lote estas opciones no harian nada, asi que se avisa en vez de ignorarlas
+    if let Some(lote) = argumentos
+        .iter()
+        .find(|a| *a == "--lote" || *a == "--lote-continuo")
+        && let Some(opcion) = argumentos
+            .iter()
+            .find(|a| OPCIONES_SIN_LOTE.contains(&a.as_str()))
+    {
+        return Err(ErrorDeArgumento::OpcionesIncompatibles {
+            opcion: opcion.clone(),
+            con: lote.clone(),
+        });
+    }
+
+    // el angulo se valida al final porque los limites pueden venir despues
+    if let Some(angulo) = configuracion.angulo {
+        let limites = &configuracion.limites;
+        let valido = (limites.angulo_min..=limites.angulo_max).contains(&angulo)
+            && angulo_de_sol_valido(angulo);
+        if !valido {
+            let opcion = argumentos
+                .iter()
+                .rev()
+                .find(|a| *a == "--angle" || *a == "--angulo")
+                .map_or("--angulo", String::as_str);
+            return Err(valor_invalido(opcion));
+        }
+    }
+    Ok(configuracion)
+}
+
+/// Funcion interna que arma el error de valor invalido
+fn valor_invalido(opcion: &str) -> ErrorDeArgumento {
+    ErrorDeArgumento::ValorInvalido {
+        opcion: opcion.to_string(),
+    }
+}
+
+/// Funcion interna que parsea el valor de una opcion
+fn parsear<T: FromStr>(valor: &str, opcion: &str) -> Result<T, ErrorDeArgumento> {
+    valor.parse().map_err(|_| valor_invalido(opcion))
+}
+
+#[cfg(test)]
+fn argumentos(texto: &str) -> Vec<String> {
+    texto.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn sin_argumentos_es_lo_de_siempre() {
+    assert_eq!(parsear_argumentos(&[]), Ok(Configuracion::default()));
+}
+
+#[test]
+fn opciones_en_cualquier_orden() {
+    let configuracion = parsear_argumentos(&argumentos(
+        "escena.txt --precision 4 --angle 30 --output sal.txt --intervalos --x-min -5 --coma-decimal",
+    ))
+    .unwrap();
+    assert_eq!(configuracion.entrada.as_deref(), Some("escena.txt"));
+    assert_eq!(configuracion.salida.as_deref(), Some("sal.txt"));
+    assert_eq!(configuracion.angulo, Some(30.0));
+    assert_eq!(configuracion.numeros.decimales, 4);
+    assert_eq!(configuracion.numeros.separador, SeparadorDecimal::Coma);
+    assert_eq!(configuracion.limites.x_min, -5.0);
+    assert!(configuracion.reportes.intervalos);
+    assert!(!configuracion.reportes.huecos);
+    assert_eq!(configuracion.idioma, None);
+}
+
+#[test]
+fn guion_es_la_entrada_estandar() {
+    let configuracion = parsear_argumentos(&argumentos("- --atribucion")).unwrap();
+    assert_eq!(configuracion.entrada, None);
+    assert_eq!(
+        parsear_argumentos(&argumentos("- otra.txt")),
+        Err(ErrorDeArgumento::EntradaRepetida {
+            entrada: "otra.txt".to_string()
+        })
+    );
+}
+
+#[test]
+fn errores_de_argumentos() {
+    let valor_invalido = |opcion: &str| {
+        Err(ErrorDeArgumento::ValorInvalido {
+            opcion: opcion.to_string(),
+        })
+    };
+    assert_eq!(
+        parsear_argumentos(&argumentos("--precision")),
+        valor_invalido("--precision")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--precision 99")),
+        valor_invalido("--precision")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--angle 85")),
+        valor_invalido("--angle")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--angulos 0:10:5")),
+        valor_invalido("--angulos")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--output-format xml")),
+        valor_invalido("--output-format")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--diagnosticos yaml")),
+        valor_invalido("--diagnosticos")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--mapa-angulos 0:90:1")),
+        valor_invalido("--mapa-angulos")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--animacion-segundos 0")),
+        valor_invalido("--animacion-segundos")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--animacion-angulos 80:10:1")),
+        valor_invalido("--animacion-angulos")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--ancho 0")),
+        valor_invalido("--ancho")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--idioma fr")),
+        valor_invalido("--idioma")
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--lote-continuo --coma-decimal")),
+        Err(ErrorDeArgumento::OpcionesIncompatibles {
+            opcion: "--coma-decimal".to_string(),
+            con: "--lote-continuo".to_string(),
+        })
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--diagnosticos texto --lote")),
+        Err(ErrorDeArgumento::OpcionesIncompatibles {
+            opcion: "--diagnosticos".to_string(),
+            con: "--lote".to_string(),
+        })
+    );
+    assert_eq!(
+        parsear_argumentos(&argumentos("--inventada")),
+        Err(ErrorDeArgumento::OpcionDesconocida {
+            opcion: "--inventada".to_string()
+        })
+    );
+}
+
+#[test]
+fn la_curva_sola_pide_el_dibujo() {
+    let configuracion = parsear_argumentos(&argumentos("--curva")).unwrap();
+    assert_eq!(configuracion.dibujo, Some(JuegoDeCaracteres::Ascii));
+    let configuracion = parsear_argumentos(&argumentos("--unicode --curva")).unwrap();
+    assert_eq!(configuracion.dibujo, Some(JuegoDeCaracteres::Unicode));
+}
+
+#[test]
+fn el_angulo_se_valida_con_los_limites_finales() {
+    let configuracion = parsear_argumentos(&argumentos("--angulo 120 --ambos-lados")).unwrap();
+    assert_eq!(configuracion.angulo, Some(120.0));
+    let configuracion = parsear_argumentos(&argumentos("--angulo 5 --angulo-min 1")).unwrap();
+    assert_eq!(configuracion.angulo, Some(5.0));
+}
+
+#[test]
+fn codigos_distintos_por_categoria() {
+    let categorias = [
+        CategoriaDeError::Io,
+        CategoriaDeError::Estructura,
+        CategoriaDeError::Numero,
+        CategoriaDeError::Rango,
+        CategoriaDeError::Formato,
+    ];
+    let mut codigos: Vec<u8> = categorias.into_iter().map(codigo_de_salida).collect();
+    codigos.push(SALIDA_USO);
+    codigos.sort_unstable();
+    codigos.dedup();
+    assert_eq!(codigos.len(), categorias.len() + 1);
+    assert!(!codigos.contains(&0));
+}
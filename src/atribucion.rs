@@ -0,0 +1,144 @@
+/// Archivo para repartir la sombra total entre los flatlanders que la generan
+use crate::intervalo::Intervalo;
+
+/// Cuanto aporta un flatlander a la sombra total
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Atribucion {
+    /// Longitud de su propia sombra
+    pub propia: f64,
+    /// Parte de su sombra que no cubre ninguna otra
+    pub exclusiva: f64,
+    /// Parte de su sombra que comparte con al menos otra
+    pub compartida: f64,
+    /// Reparto justo: cada tramo compartido se divide en partes iguales entre las sombras que lo cubren
+    pub reparto: f64,
+}
+
+/// Devuelve la atribucion de cada sombra, en el mismo orden que se recibieron
+///
+/// Barre los extremos de izquierda a derecha y va acumulando la integral de 1/profundidad,
+/// asi el reparto de cada sombra es la diferencia de esa integral entre su fin y su inicio
+///
+/// La suma de los repartos da la longitud de la union
+pub fn atribuir_sombra(sombras: &[Intervalo]) -> Vec<Atribucion> {
+    // (posicion, es inicio, indice de la sombra)
+    let mut eventos: Vec<(f64, bool, usize)> = Vec::with_capacity(sombras.len() * 2);
+    for (indice, sombra) in sombras.iter().enumerate() {
+        eventos.push((sombra.inicio, true, indice));
+        eventos.push((sombra.fin, false, indice));
+    }
+    // en la misma posicion van primero los inicios, asi una sombra de largo cero
+    // no termina antes de empezar
+    eventos.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)));
+
+    let mut atribuciones = vec![Atribucion::default(); sombras.len()];
+    let mut integral_al_inicio = vec![0.0; sombras.len()];
+    let mut integral = 0.0;
+    let mut profundidad = 0usize;
+    // con profundidad 1 la suma de los indices activos es justo el indice de la unica sombra
+    let mut suma_activos = 0usize;
+    let mut anterior = f64::NEG_INFINITY;
+
+    for (posicion, es_inicio, indice) in eventos {
+        if profundidad > 0 && posicion > anterior {
+            let largo = posicion - anterior;
+            integral += largo / profundidad as f64;
+            if profundidad == 1 {
+                atribuciones[suma_activos].exclusiva += largo;
+            }
+        }
+        if es_inicio {
+            integral_al_inicio[indice] = integral;
+            profundidad += 1;
+            suma_activos += indice;
+        } else {
+            atribuciones[indice].reparto = integral - integral_al_inicio[indice];
+            profundidad -= 1;
+            suma_activos -= indice;
+        }
+        anterior = posicion;
+    }
+
+    for (atribucion, sombra) in atribuciones.iter_mut().zip(sombras) {
+        atribucion.propia = sombra.longitud();
+        atribucion.compartida = (atribucion.propia - atribucion.exclusiva).max(0.0);
+    }
+    atribuciones
+}
+
+#[test]
+fn atribucion_de_dos_sombras_solapadas() {
+    let sombras = vec![Intervalo::nuevo(0.0, 10.0), Intervalo::nuevo(5.0, 15.0)];
+    let atribuciones = atribuir_sombra(&sombras);
+    assert_eq!(
+        atribuciones[0],
+        Atribucion {
+            propia: 10.0,
+            exclusiva: 5.0,
+            compartida: 5.0,
+            reparto: 7.5,
+        }
+    );
+    assert_eq!(atribuciones[1].reparto, 7.5);
+}
+
+#[test]
+fn sombra_tapada_por_otra_no_tiene_exclusiva() {
+    let sombras = vec![Intervalo::nuevo(0.0, 10.0), Intervalo::nuevo(2.0, 4.0)];
+    let atribuciones = atribuir_sombra(&sombras);
+    assert_eq!(atribuciones[1].exclusiva, 0.0);
+    assert_eq!(atribuciones[1].compartida, 2.0);
+    assert_eq!(atribuciones[1].reparto, 1.0);
+    assert_eq!(atribuciones[0].exclusiva, 8.0);
+    assert_eq!(atribuciones[0].reparto, 9.0);
+}
+
+#[test]
+fn repartos_suman_la_longitud_de_la_union() {
+    let sombras = vec![
+        Intervalo::nuevo(0.0, 4.0),
+        Intervalo::nuevo(1.0, 2.0),
+        Intervalo::nuevo(3.0, 6.0),
+        Intervalo::nuevo(5.0, 7.0),
+        Intervalo::nuevo(10.0, 11.5),
+    ];
+    let total: f64 = atribuir_sombra(&sombras).iter().map(|a| a.reparto).sum();
+    let union = crate::longitud_union::longitud_union(sombras);
+    assert!((total - union).abs() < 1e-9);
+}
+
+#[test]
+fn sombras_de_largo_cero_no_rompen_el_barrido() {
+    let mut sombras = vec![Intervalo::nuevo(0.0, 10.0)];
+    sombras.extend((0..20).map(|i| Intervalo::nuevo(i as f64 * 0.5, i as f64 * 0.5)));
+    let atribuciones = atribuir_sombra(&sombras);
+    assert_eq!(atribuciones[0].reparto, 10.0);
+    assert_eq!(atribuciones[0].exclusiva, 10.0);
+    assert!(
+        atribuciones[1..]
+            .iter()
+            .all(|a| *a == Atribucion::default())
+    );
+}
+
+#[test]
+fn flatlanders_sin_altura_o_con_sol_vertical_no_suman_reparto() {
+    use crate::flatlander::Flatlander;
+    use crate::longitud_union::sombras_desde_flatlanders;
+
+    let flatlanders = vec![
+        Flatlander::nuevo(0.0, 10.0),
+        Flatlander::nuevo(3.0, 0.0),
+        Flatlander::nuevo(20.0, 0.0),
+    ];
+    let atribuciones = atribuir_sombra(&sombras_desde_flatlanders(&flatlanders, 45.0));
+    assert_eq!(atribuciones[1].reparto, 0.0);
+    assert_eq!(atribuciones[2].reparto, 0.0);
+
+    let verticales = atribuir_sombra(&sombras_desde_flatlanders(&flatlanders, 90.0));
+    assert!(
+        verticales
+            .iter()
+            .all(|a| a.reparto == 0.0 && a.propia == 0.0)
+    );
+}
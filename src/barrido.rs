@@ -0,0 +1,205 @@
+/// Archivo para evaluar la misma escena con muchos angulos ordenando una sola vez
+use crate::flatlander::Flatlander;
+use crate::geometria::desplazamiento_de_sombra;
+use crate::longitud_union::sombra_de_flatlander;
+
+/// Escena con los flatlanders ya ordenados por x
+///
+/// El inicio de cada sombra (o el fin, si el sol esta a la derecha) es siempre x, asi que el orden
+/// no depende del angulo y alcanza con ordenar una vez para evaluar cada angulo en O(n)
+#[derive(Debug)]
+pub struct EscenaOrdenada {
+    flatlanders: Vec<Flatlander>,
+}
+
+impl EscenaOrdenada {
+    /// Crea la escena ordenando los flatlanders por x
+    pub fn nueva(mut flatlanders: Vec<Flatlander>) -> Self {
+        flatlanders.sort_unstable_by(|a, b| a.x.total_cmp(&b.x));
+        Self { flatlanders }
+    }
+
+    /// Devuelve los flatlanders ordenados por x
+    pub fn flatlanders(&self) -> &[Flatlander] {
+        &self.flatlanders
+    }
+
+    /// Devuelve la longitud de la union de las sombras para un angulo, en O(n)
+    ///
+    /// Si las sombras van a la izquierda se recorre al reves con las coordenadas espejadas,
+    /// asi los inicios siguen quedando ordenados
+    pub fn longitud_en_angulo(&self, angulo: f64) -> f64 {
+        let desplazamientos = self
+            .flatlanders
+            .iter()
+            .map(|f| (f.x, desplazamiento_de_sombra(f.h, angulo)));
+        if angulo > 90.0 {
+            longitud_de_ordenados(desplazamientos.rev().map(|(x, d)| (-x, -x - d)))
+        } else {
+            longitud_de_ordenados(desplazamientos.map(|(x, d)| (x, x + d)))
+        }
+    }
+
+    /// Devuelve un par (angulo, longitud) por cada angulo recibido
+    pub fn longitudes_por_angulo(&self, angulos: &[f64]) -> Vec<(f64, f64)> {
+        angulos
+            .iter()
+            .map(|&angulo| (angulo, self.longitud_en_angulo(angulo)))
+            .collect()
+    }
+}
+
+/// Funcion interna que une intervalos (inicio, fin) que ya vienen ordenados por inicio
+fn longitud_de_ordenados(mut intervalos: impl Iterator<Item = (f64, f64)>) -> f64 {
+    let Some((mut inicio, mut fin)) = intervalos.next() else {
+        return 0.0;
+    };
+    let mut longitud_total = 0.0;
+    for (inicio_iterado, fin_iterado) in intervalos {
+        if inicio_iterado <= fin {
+            if fin_iterado > fin {
+                fin = fin_iterado;
+            }
+        } else {
+            longitud_total += fin - inicio;
+            inicio = inicio_iterado;
+            fin = fin_iterado;
+        }
+    }
+    longitud_total + (fin - inicio)
+}
+
+/// Cantidad maxima de angulos que puede dar un rango, para que un paso diminuto no pida memoria de mas
+pub const MAX_ANGULOS_EN_RANGO: usize = 1_000_000;
+
+/// Devuelve los angulos desde, desde + paso, ... hasta llegar a hasta (incluido si cae justo)
+///
+/// Se calcula cada angulo como desde + i * paso para no ir acumulando error de redondeo
+///
+/// Devuelve None si el paso no es positivo, si hasta es menor que desde, si algun extremo no es
+/// finito o si saldrian mas de MAX_ANGULOS_EN_RANGO angulos
+pub fn angulos_en_rango(desde: f64, hasta: f64, paso: f64) -> Option<Vec<f64>> {
+    let valido = paso > 0.0 && paso.is_finite() && desde.is_finite() && hasta >= desde;
+    if !valido {
+        return None;
+    }
+    // la cuenta se hace en f64 para que un paso chico no desborde al pasarla a usize
+    let cantidad = ((hasta - desde) / paso + 1e-9).floor() + 1.0;
+    if !cantidad.is_finite() || cantidad > MAX_ANGULOS_EN_RANGO as f64 {
+        return None;
+    }
+    Some(
+        (0..cantidad as usize)
+            .map(|i| desde + i as f64 * paso)
+            .collect(),
+    )
+}
+
+/// Devuelve desde donde hasta donde llegan las sombras de los flatlanders en todos los angulos
+///
+/// La punta de cada sombra se mueve de forma monotona con el angulo, asi que alcanza con mirar el
+/// menor y el mayor. El rango que devuelve nunca es vacio, como explica rango_no_vacio
+pub fn rango_de_sombras(flatlanders: &[Flatlander], angulos: &[f64]) -> (f64, f64) {
+    let menor = angulos.iter().copied().reduce(f64::min);
+    let mayor = angulos.iter().copied().reduce(f64::max);
+    let (desde, hasta) = menor
+        .zip(mayor)
+        .into_iter()
+        .flat_map(|extremos| {
+            flatlanders.iter().flat_map(move |flatlander| {
+                [extremos.0, extremos.1].map(|angulo| sombra_de_flatlander(flatlander, angulo))
+            })
+        })
+        .fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(desde, hasta), sombra| (desde.min(sombra.inicio), hasta.max(sombra.fin)),
+        );
+    rango_no_vacio(desde, hasta)
+}
+
+/// Agranda un rango para que tenga ancho y se pueda dibujar
+///
+/// Si esta dado vuelta, como cuando no hay sombras, queda de 0 a 1, y si es un solo punto se le
+/// agrega media unidad de cada lado
+pub fn rango_no_vacio(desde: f64, hasta: f64) -> (f64, f64) {
+    if desde > hasta {
+        (0.0, 1.0)
+    } else if desde == hasta {
+        (desde - 0.5, hasta + 0.5)
+    } else {
+        (desde, hasta)
+    }
+}
+
+/// Parsea un rango con formato desde:hasta:paso, por ejemplo 10:80:0.5
+pub fn parsear_rango_de_angulos(rango: &str) -> Option<Vec<f64>> {
+    let mut partes = rango.split(':').map(|p| p.trim().parse::<f64>());
+    let desde = partes.next()?.ok()?;
+    let hasta = partes.next()?.ok()?;
+    let paso = partes.next()?.ok()?;
+    if partes.next().is_some() {
+        return None;
+    }
+    angulos_en_rango(desde, hasta, paso)
+}
+
+#[test]
+fn coincide_con_longitud_union_en_cada_angulo() {
+    use crate::longitud_union::{longitud_union, sombras_desde_flatlanders};
+    let mut flatlanders = crate::pruebas::flatlanders_del_ejemplo();
+    flatlanders.push(Flatlander::nuevo(400.0, 3.0));
+    let copia: Vec<Flatlander> = flatlanders
+        .iter()
+        .map(|f| Flatlander::nuevo(f.x, f.h))
+        .collect();
+    let escena = EscenaOrdenada::nueva(copia);
+    for angulo in [10.0, 30.0, 45.0, 80.0, 90.0, 100.0, 135.0, 170.0] {
+        let esperado = longitud_union(sombras_desde_flatlanders(&flatlanders, angulo));
+        assert!((escena.longitud_en_angulo(angulo) - esperado).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn escena_vacia_da_cero() {
+    let escena = EscenaOrdenada::nueva(Vec::new());
+    assert_eq!(escena.longitud_en_angulo(45.0), 0.0);
+}
+
+#[test]
+fn rango_de_angulos_incluye_el_final() {
+    let angulos = parsear_rango_de_angulos("10:80:0.5").unwrap();
+    assert_eq!(angulos.len(), 141);
+    assert_eq!(angulos[0], 10.0);
+    assert_eq!(*angulos.last().unwrap(), 80.0);
+}
+
+#[test]
+fn rango_de_sombras_mira_los_angulos_extremos() {
+    let flatlanders = crate::pruebas::dos_flatlanders(3.0);
+    let (desde, hasta) = rango_de_sombras(&flatlanders, &[60.0, 45.0, 135.0]);
+    // a 45 la de 3 llega a 5 y a 135 la de 0 llega a -2
+    assert!((desde + 2.0).abs() < 1e-9 && (hasta - 5.0).abs() < 1e-9);
+    assert_eq!(rango_de_sombras(&[], &[45.0]), (0.0, 1.0));
+    assert_eq!(rango_de_sombras(&flatlanders, &[]), (0.0, 1.0));
+    let al_mediodia = rango_de_sombras(&[Flatlander::nuevo(4.0, 2.0)], &[90.0]);
+    assert_eq!(al_mediodia, (3.5, 4.5));
+}
+
+#[test]
+fn rango_de_angulos_invalido() {
+    assert!(parsear_rango_de_angulos("10:80").is_none());
+    assert!(parsear_rango_de_angulos("10:80:0").is_none());
+    assert!(parsear_rango_de_angulos("80:10:1").is_none());
+    assert!(parsear_rango_de_angulos("a:80:1").is_none());
+}
+
+#[test]
+fn rango_de_angulos_con_demasiados_angulos() {
+    assert!(parsear_rango_de_angulos("10:80:1e-300").is_none());
+    assert!(parsear_rango_de_angulos("10:80:1e-12").is_none());
+    assert!(angulos_en_rango(10.0, f64::INFINITY, 1.0).is_none());
+    assert!(angulos_en_rango(f64::NEG_INFINITY, 80.0, 1.0).is_none());
+    assert!(angulos_en_rango(10.0, f64::NAN, 1.0).is_none());
+    let justo = angulos_en_rango(0.0, (MAX_ANGULOS_EN_RANGO - 1) as f64, 1.0).unwrap();
+    assert_eq!(justo.len(), MAX_ANGULOS_EN_RANGO);
+}
@@ -0,0 +1,148 @@
+/// Archivo para medir cuantas sombras cubren cada punto del piso
+use crate::intervalo::Intervalo;
+
+/// Tramo del perfil de profundidad: el intervalo y cuantas sombras lo cubren
+pub type TramoDeProfundidad = (Intervalo, usize);
+
+/// Devuelve la profundidad de cobertura como funcion escalonada
+///
+/// Barre los extremos de izquierda a derecha sumando 1 en cada inicio y restando 1 en cada fin
+///
+/// Solo aparecen los tramos con profundidad mayor a cero, ordenados y sin tramos vecinos con la misma profundidad
+pub fn perfil_de_profundidad(sombras: &[Intervalo]) -> Vec<TramoDeProfundidad> {
+    let mut eventos: Vec<(f64, i64)> = Vec::with_capacity(sombras.len() * 2);
+    for sombra in sombras {
+        eventos.push((sombra.inicio, 1));
+        eventos.push((sombra.fin, -1));
+    }
+    eventos.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut perfil: Vec<TramoDeProfundidad> = Vec::new();
+    let mut profundidad: i64 = 0;
+    let mut anterior = f64::NEG_INFINITY;
+    let mut i = 0;
+
+    while i < eventos.len() {
+        let posicion = eventos[i].0;
+        if profundidad > 0 && posicion > anterior {
+            agregar_tramo(&mut perfil, anterior, posicion, profundidad as usize);
+        }
+        // se aplican juntos todos los eventos en la misma posicion
+        while i < eventos.len() && eventos[i].0 == posicion {
+            profundidad += eventos[i].1;
+            i += 1;
+        }
+        anterior = posicion;
+    }
+    perfil
+}
+
+/// Agrega un tramo al perfil, pegandolo al anterior si tiene la misma profundidad y se tocan
+fn agregar_tramo(perfil: &mut Vec<TramoDeProfundidad>, inicio: f64, fin: f64, profundidad: usize) {
+    if let Some((ultimo, profundidad_ultimo)) = perfil.last_mut()
+        && *profundidad_ultimo == profundidad
+        && ultimo.fin == inicio
+    {
+        ultimo.fin = fin;
+        return;
+    }
+    perfil.push((Intervalo::nuevo(inicio, fin), profundidad));
+}
+
+/// Devuelve la profundidad maxima y los tramos donde se alcanza
+///
+/// Si el perfil esta vacio devuelve None
+pub fn profundidad_maxima(perfil: &[TramoDeProfundidad]) -> Option<(usize, Vec<Intervalo>)> {
+    let maxima = perfil.iter().map(|(_, profundidad)| *profundidad).max()?;
+    let donde = perfil
+        .iter()
+        .filter(|(_, profundidad)| *profundidad == maxima)
+        .map(|(intervalo, _)| *intervalo)
+        .collect();
+    Some((maxima, donde))
+}
+
+/// Devuelve la longitud cubierta por al menos k sombras
+///
+/// Con k igual a 0 o 1 es la longitud de la union
+pub fn longitud_con_cobertura_al_menos(perfil: &[TramoDeProfundidad], k: usize) -> f64 {
+    perfil
+        .iter()
+        .filter(|(_, profundidad)| *profundidad >= k)
+        .map(|(intervalo, _)| intervalo.longitud())
+        .sum()
+}
+
+#[test]
+fn perfil_de_dos_sombras_solapadas() {
+    let sombras = vec![Intervalo::nuevo(0.0, 10.0), Intervalo::nuevo(5.0, 15.0)];
+    let perfil = perfil_de_profundidad(&sombras);
+    assert_eq!(
+        perfil,
+        vec![
+            (Intervalo::nuevo(0.0, 5.0), 1),
+            (Intervalo::nuevo(5.0, 10.0), 2),
+            (Intervalo::nuevo(10.0, 15.0), 1),
+        ]
+    );
+}
+
+#[test]
+fn perfil_pega_sombras_que_se_tocan_en_un_punto() {
+    let sombras = vec![Intervalo::nuevo(5.0, 8.0), Intervalo::nuevo(3.0, 5.0)];
+    let perfil = perfil_de_profundidad(&sombras);
+    assert_eq!(perfil, vec![(Intervalo::nuevo(3.0, 8.0), 1)]);
+}
+
+#[test]
+fn perfil_deja_afuera_los_huecos() {
+    let sombras = vec![Intervalo::nuevo(0.0, 1.0), Intervalo::nuevo(2.0, 3.0)];
+    let perfil = perfil_de_profundidad(&sombras);
+    assert_eq!(
+        perfil,
+        vec![
+            (Intervalo::nuevo(0.0, 1.0), 1),
+            (Intervalo::nuevo(2.0, 3.0), 1)
+        ]
+    );
+}
+
+#[test]
+fn maxima_profundidad_y_donde_ocurre() {
+    let sombras = vec![
+        Intervalo::nuevo(0.0, 4.0),
+        Intervalo::nuevo(1.0, 2.0),
+        Intervalo::nuevo(3.0, 6.0),
+        Intervalo::nuevo(5.0, 7.0),
+    ];
+    let perfil = perfil_de_profundidad(&sombras);
+    let (maxima, donde) = profundidad_maxima(&perfil).unwrap();
+    assert_eq!(maxima, 2);
+    assert_eq!(
+        donde,
+        vec![
+            Intervalo::nuevo(1.0, 2.0),
+            Intervalo::nuevo(3.0, 4.0),
+            Intervalo::nuevo(5.0, 6.0),
+        ]
+    );
+}
+
+#[test]
+fn maxima_profundidad_sin_sombras() {
+    assert!(profundidad_maxima(&[]).is_none());
+}
+
+#[test]
+fn longitud_con_k_coberturas() {
+    let sombras = vec![
+        Intervalo::nuevo(0.0, 10.0),
+        Intervalo::nuevo(5.0, 15.0),
+        Intervalo::nuevo(8.0, 9.0),
+    ];
+    let perfil = perfil_de_profundidad(&sombras);
+    assert!((longitud_con_cobertura_al_menos(&perfil, 1) - 15.0).abs() < 1e-12);
+    assert!((longitud_con_cobertura_al_menos(&perfil, 2) - 5.0).abs() < 1e-12);
+    assert!((longitud_con_cobertura_al_menos(&perfil, 3) - 1.0).abs() < 1e-12);
+    assert_eq!(longitud_con_cobertura_al_menos(&perfil, 4), 0.0);
+}
@@ -0,0 +1,385 @@
+/// Archivo para calcular de forma exacta la longitud de la union en funcion del angulo
+///
+/// Con c = 1 / tan(angulo) cada sombra es [x, x + h * c], asi que la union es lineal en c
+/// por tramos y en funcion del angulo queda constante + coeficiente * cot(angulo)
+use crate::flatlander::Flatlander;
+use crate::geometria::longitud_de_sombra;
+#[cfg(test)]
+use crate::pruebas::flatlanders_variados;
+use std::f64::consts::PI;
+
+/// Tramo donde la longitud de la union vale constante + coeficiente * cot(angulo)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TramoPorAngulo {
+    /// Angulo donde empieza el tramo, en grados
+    pub angulo_desde: f64,
+    /// Angulo donde termina el tramo, en grados
+    pub angulo_hasta: f64,
+    /// Termino constante
+    pub constante: f64,
+    /// Coeficiente que multiplica a la cotangente
+    pub coeficiente: f64,
+}
+
+impl TramoPorAngulo {
+    /// Evalua el tramo en un angulo, no se fija que el angulo caiga adentro
+    pub fn evaluar(&self, angulo: f64) -> f64 {
+        self.constante + self.coeficiente * longitud_de_sombra(1.0, angulo)
+    }
+
+    /// Integral del tramo con el angulo en grados
+    ///
+    /// La primitiva de cot en grados es 180 / pi * ln(sen)
+    pub fn integral(&self) -> f64 {
+        let a = self.angulo_desde * PI / 180.0;
+        let b = self.angulo_hasta * PI / 180.0;
+        self.constante * (self.angulo_hasta - self.angulo_desde)
+            + self.coeficiente * 180.0 / PI * (b.sin() / a.sin()).ln()
+    }
+}
+
+/// La longitud de la union como funcion del angulo, armada por tramos ordenados
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoberturaPorAngulo {
+    /// Tramos ordenados por angulo, uno pegado al otro
+    pub tramos: Vec<TramoPorAngulo>,
+}
+
+impl CoberturaPorAngulo {
+    /// Devuelve los angulos criticos, donde cambia la formula, sin contar los extremos del rango
+    pub fn angulos_criticos(&self) -> Vec<f64> {
+        self.tramos
+            .iter()
+            .skip(1)
+            .map(|tramo| tramo.angulo_desde)
+            .collect()
+    }
+
+    /// Evalua la funcion en un angulo, None si esta fuera del rango
+    pub fn evaluar(&self, angulo: f64) -> Option<f64> {
+        self.tramos
+            .iter()
+            .find(|tramo| tramo.angulo_desde <= angulo && angulo <= tramo.angulo_hasta)
+            .map(|tramo| tramo.evaluar(angulo))
+    }
+
+    /// Devuelve el angulo y el valor maximo
+    ///
+    /// Cada tramo es monotono, asi que alcanza con mirar los extremos de los tramos
+    pub fn maximo(&self) -> Option<(f64, f64)> {
+        self.extremos().max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Devuelve el angulo y el valor minimo
+    pub fn minimo(&self) -> Option<(f64, f64)> {
+        self.extremos().min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Integral de la longitud sobre todo el rango, con el angulo en grados
+    pub fn integral(&self) -> f64 {
+        self.tramos.iter().map(TramoPorAngulo::integral).sum()
+    }
+
+    /// Funcion interna con los pares (angulo, valor) en los extremos de cada tramo
+    fn extremos(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.tramos.iter().flat_map(|tramo| {
+            [
+                (tramo.angulo_desde, tramo.evaluar(tramo.angulo_desde)),
+                (tramo.angulo_hasta, tramo.evaluar(tramo.angulo_hasta)),
+            ]
+        })
+    }
+}
+
+/// Devuelve la longitud de la union en funcion del angulo para angulos entre desde y hasta
+///
+/// Los angulos tienen que cumplir 0 < desde <= hasta < 180, sino devuelve None
+///
+/// Pasando 90 las sombras van a la izquierda, eso se resuelve espejando la escena
+pub fn cobertura_por_angulo(
+    flatlanders: &[Flatlander],
+    desde: f64,
+    hasta: f64,
+) -> Option<CoberturaPorAngulo> {
+    let rango_valido = desde > 0.0 && desde <= hasta && hasta < 180.0;
+    if !rango_valido {
+        return None;
+    }
+    let mut tramos = Vec::new();
+    if desde <= 90.0 {
+        let escena: Vec<(f64, f64)> = flatlanders.iter().map(|f| (f.x, f.h)).collect();
+        tramos.extend(tramos_a_la_derecha(escena, desde, hasta.min(90.0)));
+    }
+    if hasta > 90.0 {
+        // con el angulo espejado 180 - angulo la cotangente cambia de signo
+        let espejada: Vec<(f64, f64)> = flatlanders.iter().map(|f| (-f.x, f.h)).collect();
+        let desde_espejado = 180.0 - hasta;
+        let hasta_espejado = 180.0 - desde.max(90.0);
+        let mut izquierda: Vec<TramoPorAngulo> =
+            tramos_a_la_derecha(espejada, desde_espejado, hasta_espejado)
+                .into_iter()
+                .map(|tramo| TramoPorAngulo {
+                    angulo_desde: 180.0 - tramo.angulo_hasta,
+                    angulo_hasta: 180.0 - tramo.angulo_desde,
+                    constante: tramo.constante,
+                    coeficiente: -tramo.coeficiente,
+                })
+                .collect();
+        izquierda.reverse();
+        tramos.extend(izquierda);
+    }
+    Some(CoberturaPorAngulo { tramos })
+}
+
+/// Cotangente en grados, forzada a cero en 90 igual que la sombra
+fn cotangente(angulo: f64) -> f64 {
+    if angulo == 90.0 {
+        0.0
+    } else {
+        longitud_de_sombra(1.0, angulo)
+    }
+}
+
+/// Calcula los tramos para angulos entre desde y hasta, los dos en (0, 90]
+///
+/// Ordenados por x, la parte cubierta de [x_i, x_i+1] mide min(x_i+1 - x_i, R_i(c) - x_i), donde
+/// R_i(c) es el maximo de x_j + h_j * c para j <= i. Ese maximo es la envolvente superior de
+/// rectas, que se mantiene en una pila como en el convex hull trick. Cada termino aporta su valor
+/// en el primer c y los cambios de pendiente, y con eso se arma la funcion total
+fn tramos_a_la_derecha(mut escena: Vec<(f64, f64)>, desde: f64, hasta: f64) -> Vec<TramoPorAngulo> {
+    escena.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    let c_inicial = cotangente(hasta);
+    let c_final = cotangente(desde);
+
+    let mut valor_inicial = 0.0;
+    let mut pendiente_inicial = 0.0;
+    let mut cambios: Vec<(f64, f64)> = Vec::new();
+    // cada elemento es la recta (x, h); la de arriba domina para c chicos
+    let mut envolvente: Vec<(f64, f64)> = Vec::new();
+
+    for i in 0..escena.len() {
+        agregar_recta(&mut envolvente, escena[i]);
+        let x_i = escena[i].0;
+        let hueco = escena
+            .get(i + 1)
+            .map_or(f64::INFINITY, |siguiente| siguiente.0 - x_i);
+
+        let mut bajo = 0.0;
+        let mut pendiente_anterior: Option<f64> = None;
+        for k in (0..envolvente.len()).rev() {
+            let (x_k, h_k) = envolvente[k];
+            let alto = if k > 0 {
+                cruce(envolvente[k], envolvente[k - 1])
+            } else {
+                f64::INFINITY
+            };
+            if alto <= c_inicial {
+                bajo = alto;
+                continue;
+            }
+            let desde_c = f64::max(bajo, c_inicial);
+            if desde_c >= c_final {
+                break;
+            }
+            let ordenada = x_k - x_i;
+            match pendiente_anterior {
+                None => {
+                    let valor = ordenada + h_k * c_inicial;
+                    if valor >= hueco {
+                        valor_inicial += hueco;
+                        break;
+                    }
+                    valor_inicial += valor;
+                    pendiente_inicial += h_k;
+                }
+                Some(pendiente) => cambios.push((desde_c, h_k - pendiente)),
+            }
+            if h_k > 0.0 {
+                let c_tope = (hueco - ordenada) / h_k;
+                if c_tope < alto.min(c_final) {
+                    cambios.push((c_tope, -h_k));
+                    break;
+                }
+            }
+            pendiente_anterior = Some(h_k);
+            bajo = alto;
+        }
+    }
+
+    armar_tramos(
+        valor_inicial,
+        pendiente_inicial,
+        cambios,
+        c_inicial,
+        c_final,
+    )
+}
+
+/// Abscisa donde la recta de arriba deja de dominar a la de abajo
+fn cruce(arriba: (f64, f64), abajo: (f64, f64)) -> f64 {
+    (arriba.0 - abajo.0) / (abajo.1 - arriba.1)
+}
+
+/// Agrega una recta con x mayor o igual que todas las de la pila y saca las que dejan de servir
+fn agregar_recta(envolvente: &mut Vec<(f64, f64)>, recta: (f64, f64)) {
+    while let Some(&tope) = envolvente.last() {
+        if recta.1 >= tope.1 {
+            envolvente.pop();
+        } else {
+            break;
+        }
+    }
+    if let Some(&tope) = envolvente.last()
+        && tope.0 == recta.0
+    {
+        // misma x y menor altura, nunca queda arriba
+        return;
+    }
+    while envolvente.len() >= 2 {
+        let tope = envolvente[envolvente.len() - 1];
+        let debajo = envolvente[envolvente.len() - 2];
+        if cruce(recta, tope) >= cruce(tope, debajo) {
+            envolvente.pop();
+        } else {
+            break;
+        }
+    }
+    envolvente.push(recta);
+}
+
+/// Arma los tramos por angulo a partir del valor y la pendiente en c_inicial y los cambios de pendiente
+fn armar_tramos(
+    valor_inicial: f64,
+    pendiente_inicial: f64,
+    mut cambios: Vec<(f64, f64)>,
+    c_inicial: f64,
+    c_final: f64,
+) -> Vec<TramoPorAngulo> {
+    cambios.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    // tramos en c: (c desde, c hasta, valor en c desde, pendiente)
+    let mut tramos_en_c: Vec<(f64, f64, f64, f64)> = Vec::new();
+    let mut c_anterior = c_inicial;
+    let mut valor = valor_inicial;
+    let mut pendiente = pendiente_inicial;
+    let mut i = 0;
+
+    while i < cambios.len() {
+        let c = cambios[i].0;
+        let mut cambio_total = 0.0;
+        while i < cambios.len() && cambios[i].0 == c {
+            cambio_total += cambios[i].1;
+            i += 1;
+        }
+        if cambio_total == 0.0 || c <= c_anterior || c >= c_final {
+            pendiente += cambio_total;
+            continue;
+        }
+        tramos_en_c.push((c_anterior, c, valor, pendiente));
+        valor += pendiente * (c - c_anterior);
+        pendiente += cambio_total;
+        c_anterior = c;
+    }
+    tramos_en_c.push((c_anterior, c_final, valor, pendiente));
+
+    // c crece cuando el angulo baja, asi que se recorren al reves
+    tramos_en_c
+        .into_iter()
+        .rev()
+        .map(|(c_desde, c_hasta, valor, pendiente)| TramoPorAngulo {
+            angulo_desde: angulo_desde_cotangente(c_hasta),
+            angulo_hasta: angulo_desde_cotangente(c_desde),
+            constante: valor - pendiente * c_desde,
+            coeficiente: pendiente,
+        })
+        .collect()
+}
+
+/// Pasa de la cotangente al angulo en grados
+fn angulo_desde_cotangente(c: f64) -> f64 {
+    90.0 - c.atan() * 180.0 / PI
+}
+
+#[test]
+fn coincide_con_longitud_union_en_todo_el_rango() {
+    use crate::longitud_union::{longitud_union, sombras_desde_flatlanders};
+    let flatlanders = flatlanders_variados();
+    let cobertura = cobertura_por_angulo(&flatlanders, 5.0, 175.0).unwrap();
+    let mut angulo = 5.0;
+    while angulo <= 175.0 {
+        let esperado = longitud_union(sombras_desde_flatlanders(&flatlanders, angulo));
+        let obtenido = cobertura.evaluar(angulo).unwrap();
+        assert!(
+            (obtenido - esperado).abs() < 1e-6,
+            "angulo {angulo}: {obtenido} vs {esperado}"
+        );
+        angulo += 0.25;
+    }
+}
+
+#[test]
+fn angulos_criticos_donde_una_punta_llega_al_siguiente() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(10.0, 1.0)];
+    let cobertura = cobertura_por_angulo(&flatlanders, 10.0, 80.0).unwrap();
+    let criticos = cobertura.angulos_criticos();
+    // en 45 la punta de la primera llega a x = 10 y despues se pasa a la punta de la segunda
+    let cruce_de_puntas = 90.0 - (10.0_f64 / 9.0).atan() * 180.0 / PI;
+    assert_eq!(criticos.len(), 2);
+    assert!((criticos[0] - cruce_de_puntas).abs() < 1e-9);
+    assert!((criticos[1] - 45.0).abs() < 1e-9);
+}
+
+#[test]
+fn maximo_y_minimo_en_los_extremos_del_rango() {
+    let flatlanders = flatlanders_variados();
+    let cobertura = cobertura_por_angulo(&flatlanders, 10.0, 80.0).unwrap();
+    let (angulo_max, _) = cobertura.maximo().unwrap();
+    let (angulo_min, _) = cobertura.minimo().unwrap();
+    assert!((angulo_max - 10.0).abs() < 1e-9);
+    assert!((angulo_min - 80.0).abs() < 1e-9);
+}
+
+#[test]
+fn integral_coincide_con_simpson() {
+    let flatlanders = flatlanders_variados();
+    let cobertura = cobertura_por_angulo(&flatlanders, 10.0, 80.0).unwrap();
+    let pasos = 20_000;
+    let ancho = 70.0 / pasos as f64;
+    let mut suma = 0.0;
+    for i in 0..=pasos {
+        let peso = if i == 0 || i == pasos {
+            1.0
+        } else if i % 2 == 1 {
+            4.0
+        } else {
+            2.0
+        };
+        suma += peso * cobertura.evaluar(10.0 + i as f64 * ancho).unwrap();
+    }
+    let simpson = suma * ancho / 3.0;
+    assert!((cobertura.integral() - simpson).abs() < 1e-3);
+}
+
+#[test]
+fn rango_invalido_devuelve_none() {
+    let flatlanders = flatlanders_variados();
+    assert!(cobertura_por_angulo(&flatlanders, 0.0, 80.0).is_none());
+    assert!(cobertura_por_angulo(&flatlanders, 80.0, 10.0).is_none());
+    assert!(cobertura_por_angulo(&flatlanders, 10.0, 180.0).is_none());
+}
+
+#[test]
+fn coincide_con_longitud_union_en_escena_pseudoaleatoria() {
+    use crate::longitud_union::{longitud_union, sombras_desde_flatlanders};
+    let mut generador = crate::pruebas::Generador::nuevo(42);
+    let mut siguiente = || generador.siguiente_f64();
+    let flatlanders: Vec<Flatlander> = (0..300)
+        .map(|_| Flatlander::nuevo((siguiente() * 3000.0).round(), 1.0 + siguiente() * 99.0))
+        .collect();
+    let cobertura = cobertura_por_angulo(&flatlanders, 10.0, 170.0).unwrap();
+    let mut angulo = 10.0;
+    while angulo <= 170.0 {
+        let esperado = longitud_union(sombras_desde_flatlanders(&flatlanders, angulo));
+        assert!((cobertura.evaluar(angulo).unwrap() - esperado).abs() < 1e-6);
+        angulo += 0.1;
+    }
+}
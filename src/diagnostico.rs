@@ -0,0 +1,323 @@
+/// Archivo para juntar todos los errores de una entrada y mostrarlos con su posicion
+use crate::escena_anotada::parsear_escena_anotada_con_opciones;
+use crate::formato_csv::parsear_csv_con_opciones;
+use crate::formato_json::{escapar, parsear_json};
+use crate::formatos::FormatoDeEntrada;
+use crate::lector::IteradorDeFlatlanders;
+use crate::mensajes::{Idioma, mensaje_de_error};
+use crate::parseador::{
+    CategoriaDeError, ErrorParseo, Limites, ModoDeParseo, OpcionesDeParseo,
+    parsear_linea_de_encabezado,
+};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Como se escriben los diagnosticos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatoDeDiagnosticos {
+    /// Para leer: la posicion, el mensaje y la linea con el error marcado abajo
+    Texto,
+    /// Un objeto JSON por linea, para que lo lea otro programa
+    Json,
+}
+
+impl FromStr for FormatoDeDiagnosticos {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "texto" => Ok(FormatoDeDiagnosticos::Texto),
+            "json" => Ok(FormatoDeDiagnosticos::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Un error de la entrada junto con la linea donde esta, para poder mostrarla
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostico {
+    /// El error tal como lo devuelve el parseador
+    pub error: ErrorParseo,
+    /// Texto de la linea del error, None si el error no tiene linea o la linea no existe
+    pub linea_de_origen: Option<String>,
+}
+
+impl Diagnostico {
+    /// Arma el diagnostico buscando la linea del error en la entrada
+    pub fn nuevo(error: ErrorParseo, entrada: &str) -> Self {
+        let linea_de_origen = error
+            .linea()
+            .and_then(|linea| entrada.lines().nth(linea.checked_sub(1)?))
+            .map(str::to_string);
+        Self {
+            error,
+            linea_de_origen,
+        }
+    }
+
+    /// Categoria del error
+    pub fn categoria(&self) -> CategoriaDeError {
+        self.error.categoria()
+    }
+}
+
+/// Parsea la entrada entera y devuelve todos los errores que encuentra, en el orden en que aparecen
+///
+/// En el formato clasico una linea de flatlander con error no corta el parseo: se anota y se sigue
+/// con la siguiente. Si el encabezado esta mal no se sabe cuantos flatlanders hay, asi que el resto
+/// se revisa como en el modo permisivo, hasta el final. Los otros formatos cortan en el primer
+/// error como siempre, asi que devuelven a lo sumo uno
+///
+/// Si no se pasan limites se usan los de la consigna
+pub fn diagnosticar(
+    entrada: &str,
+    formato: FormatoDeEntrada,
+    limites: Option<&Limites>,
+    opciones: OpcionesDeParseo,
+) -> Vec<Diagnostico> {
+    let errores = match formato {
+        FormatoDeEntrada::Clasico => errores_del_clasico(entrada, limites, opciones),
+        FormatoDeEntrada::Anotado => {
+            parsear_escena_anotada_con_opciones(entrada, limites, opciones)
+                .err()
+                .into_iter()
+                .collect()
+        }
+        FormatoDeEntrada::Csv => parsear_csv_con_opciones(entrada, limites, opciones)
+            .err()
+            .into_iter()
+            .collect(),
+        FormatoDeEntrada::Json => parsear_json(entrada, limites).err().into_iter().collect(),
+    };
+    errores
+        .into_iter()
+        .map(|error| Diagnostico::nuevo(error, entrada))
+        .collect()
+}
+
+/// Funcion interna que junta los errores del formato clasico
+fn errores_del_clasico(
+    entrada: &str,
+    limites: Option<&Limites>,
+    opciones: OpcionesDeParseo,
+) -> Vec<ErrorParseo> {
+    let limites = limites.copied().unwrap_or_default();
+    let permisivo = opciones.modo == ModoDeParseo::Permisivo;
+    let entrada = if permisivo {
+        entrada.strip_prefix('\u{feff}').unwrap_or(entrada)
+    } else {
+        entrada
+    };
+    let mut lineas = entrada.lines();
+    let mut errores = Vec::new();
+    let mut numero_linea = 0;
+    let n = loop {
+        numero_linea += 1;
+        match lineas.next() {
+            None => {
+                errores.push(ErrorParseo::LineaFaltante {
+                    linea: numero_linea,
+                });
+                return errores;
+            }
+            Some(linea) if permisivo && linea.trim().is_empty() => continue,
+            Some(linea) => {
+                match parsear_linea_de_encabezado(linea, numero_linea, &limites, &opciones) {
+                    Ok((_, n)) => break Some(n),
+                    Err(error) => {
+                        errores.push(error);
+                        break None;
+                    }
+                }
+            }
+        }
+    };
+    let opciones = match n {
+        Some(_) => opciones,
+        None => OpcionesDeParseo {
+            modo: ModoDeParseo::Permisivo,
+            ..opciones
+        },
+    };
+    let flatlanders = IteradorDeFlatlanders::nuevo(&mut lineas, n.unwrap_or(0), Some(&limites))
+        .con_opciones(opciones)
+        .despues_de_linea(numero_linea)
+        .seguir_despues_de_errores();
+    errores.extend(flatlanders.filter_map(Result::err));
+    errores
+}
+
+/// Escribe el diagnostico para que lo lea una persona, parecido a los errores del compilador
+///
+/// La primera linea tiene origen:linea:columna, el codigo estable y el mensaje en el idioma pedido.
+/// Si se sabe la linea se muestra abajo con el texto del error marcado con ^
+pub fn diagnostico_a_texto(diagnostico: &Diagnostico, origen: &str, idioma: Idioma) -> String {
+    let error = &diagnostico.error;
+    let mut texto = String::from(origen);
+    if let Some(linea) = error.linea() {
+        let _ = write!(texto, ":{linea}");
+    }
+    if let Some(columna) = error.columna() {
+        let _ = write!(texto, ":{columna}");
+    }
+    let _ = write!(
+        texto,
+        ": error[{}]: {}",
+        error.codigo(),
+        mensaje_de_error(error, idioma)
+    );
+    if let Some(mensaje) = error.texto().filter(|_| error.columna().is_none()) {
+        let _ = write!(texto, " ({mensaje})");
+    }
+    texto.push('\n');
+
+    if let (Some(linea), Some(origen)) = (error.linea(), &diagnostico.linea_de_origen) {
+        let margen = " ".repeat(linea.to_string().len());
+        let _ = writeln!(texto, "{margen} |");
+        let _ = writeln!(texto, "{linea} | {origen}");
+        if let Some(columna) = error.columna() {
+            // los tabs se copian para que la marca quede abajo del texto aunque la linea los tenga
+            let sangria: String = origen
+                .chars()
+                .take(columna - 1)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let ancho = error.texto().map_or(0, |t| t.chars().count()).max(1);
+            let _ = writeln!(texto, "{margen} | {sangria}{}", "^".repeat(ancho));
+        }
+    }
+    texto
+}
+
+/// Escribe el diagnostico como un objeto JSON en una sola linea, sin el fin de linea
+///
+/// Lo que el error no tenga sale como null. El codigo y la categoria no dependen del idioma, asi
+/// que son lo que conviene mirar desde otro programa
+pub fn diagnostico_a_json(diagnostico: &Diagnostico, idioma: Idioma) -> String {
+    let error = &diagnostico.error;
+    let numero = |valor: Option<usize>| valor.map_or("null".to_string(), |v| v.to_string());
+    format!(
+        "{{\"codigo\":{},\"linea\":{},\"columna\":{},\"categoria\":{},\"mensaje\":{},\"texto\":{}}}",
+        escapar(error.codigo()),
+        numero(error.linea()),
+        numero(error.columna()),
+        escapar(diagnostico.categoria().nombre()),
+        escapar(mensaje_de_error(error, idioma)),
+        error.texto().map_or("null".to_string(), escapar)
+    )
+}
+
+#[cfg(test)]
+fn diagnosticos_del_clasico(entrada: &str, modo: ModoDeParseo) -> Vec<Diagnostico> {
+    let opciones = OpcionesDeParseo {
+        modo,
+        ..OpcionesDeParseo::default()
+    };
+    diagnosticar(entrada, FormatoDeEntrada::Clasico, None, opciones)
+}
+
+#[test]
+fn junta_todos_los_errores_de_flatlanders() {
+    let entrada = "45 4\n1 abc\n2 3\n-5 1\n7\n";
+    let errores: Vec<_> = diagnosticos_del_clasico(entrada, ModoDeParseo::Normal)
+        .into_iter()
+        .map(|d| d.error)
+        .collect();
+    assert_eq!(errores.len(), 3);
+    assert!(matches!(
+        errores[0],
+        ErrorParseo::NumeroInvalido {
+            linea: 2,
+            columna: 3,
+            ..
+        }
+    ));
+    assert!(matches!(
+        errores[1],
+        ErrorParseo::FueraDeRango {
+            linea: 4,
+            columna: 1,
+            ..
+        }
+    ));
+    assert!(matches!(
+        errores[2],
+        ErrorParseo::ValorFaltante {
+            linea: 5,
+            columna: 2
+        }
+    ));
+}
+
+#[test]
+fn una_entrada_valida_no_tiene_diagnosticos() {
+    assert!(diagnosticos_del_clasico("45 2\n0 1\n2 3\n", ModoDeParseo::Normal).is_empty());
+}
+
+#[test]
+fn faltan_lineas_corta_al_final() {
+    let diagnosticos = diagnosticos_del_clasico("45 3\nx 1\n", ModoDeParseo::Normal);
+    assert_eq!(diagnosticos.len(), 2);
+    assert_eq!(
+        diagnosticos[1].error,
+        ErrorParseo::LineaFaltante { linea: 3 }
+    );
+    assert_eq!(diagnosticos[1].linea_de_origen, None);
+}
+
+#[test]
+fn con_el_encabezado_mal_sigue_con_los_flatlanders() {
+    let diagnosticos = diagnosticos_del_clasico("45 z\n1 2\n\n3 q\n", ModoDeParseo::Normal);
+    let lineas: Vec<_> = diagnosticos.iter().map(|d| d.error.linea()).collect();
+    assert_eq!(lineas, vec![Some(1), Some(4)]);
+}
+
+#[test]
+fn en_modo_estricto_marca_cada_linea_sobrante() {
+    let diagnosticos = diagnosticos_del_clasico("45 1\n1 2 3\n4 5\n6 7\n", ModoDeParseo::Estricto);
+    let lineas: Vec<_> = diagnosticos.iter().map(|d| d.error.linea()).collect();
+    assert_eq!(lineas, vec![Some(2), Some(3), Some(4)]);
+    assert!(
+        diagnosticos
+            .iter()
+            .all(|d| d.categoria() == CategoriaDeError::Estructura)
+    );
+}
+
+#[test]
+fn texto_con_la_marca_abajo_del_error() {
+    let diagnosticos = diagnosticos_del_clasico("45 1\n10 abc\n", ModoDeParseo::Normal);
+    assert_eq!(
+        diagnostico_a_texto(&diagnosticos[0], "escena.txt", Idioma::Espanol),
+        "escena.txt:2:4: error[E004]: Numero invalido\n  |\n2 | 10 abc\n  |    ^^^\n"
+    );
+    assert!(
+        diagnostico_a_texto(&diagnosticos[0], "-", Idioma::Ingles)
+            .starts_with("-:2:4: error[E004]: Invalid number\n")
+    );
+}
+
+#[test]
+fn json_en_una_linea() {
+    let diagnosticos = diagnosticos_del_clasico("45 2\n10 \"a\n", ModoDeParseo::Normal);
+    assert_eq!(
+        diagnostico_a_json(&diagnosticos[0], Idioma::Espanol),
+        "{\"codigo\":\"E004\",\"linea\":2,\"columna\":4,\"categoria\":\"numero\",\"mensaje\":\"Numero invalido\",\"texto\":\"\\\"a\"}"
+    );
+    assert_eq!(
+        diagnostico_a_json(&diagnosticos[1], Idioma::Ingles),
+        "{\"codigo\":\"E002\",\"linea\":3,\"columna\":null,\"categoria\":\"estructura\",\"mensaje\":\"Missing line\",\"texto\":null}"
+    );
+}
+
+#[test]
+fn los_otros_formatos_dan_el_primer_error() {
+    let diagnosticos = diagnosticar(
+        "x,h\n1,a\n2,b\n",
+        FormatoDeEntrada::Csv,
+        None,
+        OpcionesDeParseo::default(),
+    );
+    assert_eq!(diagnosticos.len(), 1);
+    assert_eq!(diagnosticos[0].error.linea(), Some(2));
+}
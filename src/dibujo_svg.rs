@@ -0,0 +1,344 @@
+/// Archivo para dibujar una escena en SVG armando el texto a mano, sin bibliotecas
+use crate::barrido::{rango_de_sombras, rango_no_vacio};
+use crate::flatlander::Flatlander;
+use crate::intervalo::Intervalo;
+use crate::longitud_union::{longitud_total, sombra_de_flatlander};
+use crate::numeros::FormatoDeNumeros;
+use std::fmt::Write;
+
+/// Color de cada sombra sola
+const COLOR_SOMBRA: &str = "#f4c98b";
+/// Color de la union de las sombras
+const COLOR_UNION: &str = "#8a3b12";
+/// Color de los rayos de sol
+const COLOR_RAYO: &str = "#e0a100";
+/// Cantidad aproximada de marcas en el eje
+const MARCAS_EN_EL_EJE: f64 = 8.0;
+
+/// Tamaño y estilo del dibujo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpcionesDeSvg {
+    /// Ancho del SVG en pixeles
+    pub ancho: f64,
+    /// Alto del SVG en pixeles
+    pub alto: f64,
+    /// Espacio libre alrededor del dibujo, abajo se usa el doble para el eje
+    pub margen: f64,
+    /// Usa la misma escala en x y en h, asi los rayos tienen el angulo real
+    ///
+    /// Con datos reales las posiciones suelen ser mucho mas grandes que las alturas y los
+    /// flatlanders quedan chatos, por eso por defecto cada eje tiene su escala
+    pub proporcional: bool,
+    /// Posiciones fijas de los bordes del dibujo, None para que entren justo las sombras
+    ///
+    /// Sirve cuando se dibujan varios angulos y todos tienen que usar la misma escala
+    pub rango: Option<(f64, f64)>,
+    /// Como se escriben los numeros de las marcas y del total
+    pub numeros: FormatoDeNumeros,
+}
+
+impl Default for OpcionesDeSvg {
+    fn default() -> Self {
+        Self {
+            ancho: 800.0,
+            alto: 400.0,
+            margen: 30.0,
+            proporcional: false,
+            rango: None,
+            numeros: FormatoDeNumeros {
+                decimales: 4,
+                ..FormatoDeNumeros::default()
+            },
+        }
+    }
+}
+
+/// Pasa las coordenadas de la escena a pixeles del SVG
+struct Escala {
+    x_min: f64,
+    por_unidad_x: f64,
+    por_unidad_h: f64,
+    izquierda: f64,
+    suelo: f64,
+}
+
+impl Escala {
+    /// Arma la escala para que entre todo lo que va de x_min a x_max y de 0 a h_max
+    fn nueva(x_min: f64, x_max: f64, h_max: f64, opciones: &OpcionesDeSvg) -> Self {
+        let ancho_util = (opciones.ancho - 2.0 * opciones.margen).max(1.0);
+        let alto_util = (opciones.alto - 3.0 * opciones.margen).max(1.0);
+        let mut por_unidad_x = ancho_util / (x_max - x_min);
+        let mut por_unidad_h = alto_util / h_max;
+        if opciones.proporcional {
+            por_unidad_x = por_unidad_x.min(por_unidad_h);
+            por_unidad_h = por_unidad_x;
+        }
+        Self {
+            x_min,
+            por_unidad_x,
+            por_unidad_h,
+            izquierda: opciones.margen,
+            suelo: opciones.alto - 2.0 * opciones.margen,
+        }
+    }
+
+    fn x(&self, x: f64) -> f64 {
+        self.izquierda + (x - self.x_min) * self.por_unidad_x
+    }
+
+    fn y(&self, h: f64) -> f64 {
+        self.suelo - h * self.por_unidad_h
+    }
+}
+
+/// Dibuja la escena: cada flatlander como un segmento vertical, el rayo de sol desde su cabeza
+/// hasta la punta de su sombra, cada sombra en un color claro y la union resaltada abajo
+///
+/// El eje tiene marcas con las posiciones y al pie va el total de la union. Se devuelve el SVG
+/// entero, listo para guardar en un archivo
+pub fn escena_a_svg(
+    flatlanders: &[Flatlander],
+    angulo: f64,
+    union: &[Intervalo],
+    opciones: &OpcionesDeSvg,
+) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">",
+        opciones.ancho, opciones.alto
+    );
+    let _ = writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>");
+    escribir_escena(&mut svg, flatlanders, angulo, union, opciones);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escribe los elementos de la escena sin el <svg> de afuera ni el fondo, para poder juntar
+/// varias escenas en un mismo archivo
+pub(crate) fn escribir_escena(
+    svg: &mut String,
+    flatlanders: &[Flatlander],
+    angulo: f64,
+    union: &[Intervalo],
+    opciones: &OpcionesDeSvg,
+) {
+    let sombras: Vec<Intervalo> = flatlanders
+        .iter()
+        .map(|flatlander| sombra_de_flatlander(flatlander, angulo))
+        .collect();
+    let (x_min, x_max) = match opciones.rango {
+        Some((desde, hasta)) => rango_no_vacio(desde, hasta),
+        None => rango_de_sombras(flatlanders, &[angulo]),
+    };
+    let h_max = flatlanders
+        .iter()
+        .map(|flatlander| flatlander.h)
+        .fold(0.0, f64::max);
+    let h_max = if h_max > 0.0 { h_max } else { 1.0 };
+    let escala = Escala::nueva(x_min, x_max, h_max, opciones);
+    let numeros = &opciones.numeros;
+
+    let _ = writeln!(svg, "<g fill=\"{COLOR_SOMBRA}\" fill-opacity=\"0.5\">");
+    for sombra in &sombras {
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"6\"/>",
+            escala.x(sombra.inicio),
+            escala.suelo - 6.0,
+            sombra.longitud() * escala.por_unidad_x
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = writeln!(svg, "<g fill=\"{COLOR_UNION}\">");
+    for intervalo in union {
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"4\"/>",
+            escala.x(intervalo.inicio),
+            escala.suelo,
+            intervalo.longitud() * escala.por_unidad_x
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = writeln!(
+        svg,
+        "<g stroke=\"{COLOR_RAYO}\" stroke-width=\"1\" stroke-dasharray=\"4 3\">"
+    );
+    for (flatlander, sombra) in flatlanders.iter().zip(&sombras) {
+        // la punta es el extremo de la sombra que no es el pie del flatlander
+        let punta = if sombra.inicio == flatlander.x {
+            sombra.fin
+        } else {
+            sombra.inicio
+        };
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\"/>",
+            escala.x(flatlander.x),
+            escala.y(flatlander.h),
+            escala.x(punta),
+            escala.suelo
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = writeln!(svg, "<g stroke=\"black\" stroke-width=\"2\">");
+    for (i, flatlander) in flatlanders.iter().enumerate() {
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{0:.2}\" y1=\"{1:.2}\" x2=\"{0:.2}\" y2=\"{2:.2}\"><title>{3} h={4}</title></line>",
+            escala.x(flatlander.x),
+            escala.suelo,
+            escala.y(flatlander.h),
+            escapar_xml(&flatlander.etiqueta(i)),
+            numeros.separador.localizar(flatlander.h.to_string())
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let _ = writeln!(
+        svg,
+        "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{1:.2}\" stroke=\"black\"/>",
+        escala.x(x_min),
+        escala.suelo,
+        escala.x(x_max)
+    );
+    let _ = writeln!(
+        svg,
+        "<g font-family=\"sans-serif\" font-size=\"10\" text-anchor=\"middle\">"
+    );
+    let paso = paso_de_marcas(x_max - x_min);
+    let decimales = (-paso.log10().floor()).max(0.0) as usize;
+    let primera = (x_min / paso).ceil() as i64;
+    let ultima = (x_max / paso).floor() as i64;
+    for indice in primera..=ultima {
+        let marca = indice as f64 * paso;
+        let x = escala.x(marca);
+        let _ = writeln!(
+            svg,
+            "<line x1=\"{x:.2}\" y1=\"{:.2}\" x2=\"{x:.2}\" y2=\"{:.2}\" stroke=\"black\"/>",
+            escala.suelo,
+            escala.suelo + 8.0
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{x:.2}\" y=\"{:.2}\">{}</text>",
+            escala.suelo + 20.0,
+            numeros.separador.localizar(format!("{marca:.decimales$}"))
+        );
+    }
+    let _ = writeln!(svg, "</g>");
+
+    let total = longitud_total(union);
+    let _ = writeln!(
+        svg,
+        "<text x=\"{:.2}\" y=\"{:.2}\" font-family=\"sans-serif\" font-size=\"12\">angulo {} total {}</text>",
+        opciones.margen,
+        opciones.alto - opciones.margen / 2.0,
+        numeros.separador.localizar(angulo.to_string()),
+        numeros.formatear(total)
+    );
+}
+
+/// Funcion interna que elige un paso redondo (1, 2 o 5 por una potencia de 10) para las marcas
+fn paso_de_marcas(ancho: f64) -> f64 {
+    let crudo = ancho / MARCAS_EN_EL_EJE;
+    let potencia = 10f64.powf(crudo.log10().floor());
+    let paso = [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|factor| factor * potencia)
+        .find(|paso| *paso >= crudo)
+        .unwrap_or(10.0 * potencia);
+    // los decimales que arrastra la potencia se redondean para que las marcas salgan limpias
+    let decimales = (-potencia.log10()).max(0.0) as i32;
+    let redondeo = 10f64.powi(decimales);
+    (paso * redondeo).round() / redondeo
+}
+
+/// Escapa el texto para ponerlo adentro de un elemento XML
+pub(crate) fn escapar_xml(texto: &str) -> String {
+    let mut escapado = String::with_capacity(texto.len());
+    for c in texto.chars() {
+        match c {
+            '&' => escapado.push_str("&amp;"),
+            '<' => escapado.push_str("&lt;"),
+            '>' => escapado.push_str("&gt;"),
+            '"' => escapado.push_str("&quot;"),
+            '\'' => escapado.push_str("&apos;"),
+            c => escapado.push(c),
+        }
+    }
+    escapado
+}
+
+#[cfg(test)]
+fn contar(svg: &str, patron: &str) -> usize {
+    svg.matches(patron).count()
+}
+
+#[test]
+fn dibuja_flatlanders_rayos_sombras_y_union() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(5.0, 10.0)];
+    let union = vec![Intervalo::nuevo(0.0, 15.0)];
+    let svg = escena_a_svg(&flatlanders, 45.0, &union, &OpcionesDeSvg::default());
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(contar(&svg, "<title>"), 2);
+    assert_eq!(contar(&svg, "height=\"6\""), 2);
+    assert_eq!(contar(&svg, "height=\"4\""), 1);
+    assert!(svg.contains("total 15.0000</text>"));
+}
+
+#[test]
+fn el_rayo_va_de_la_cabeza_a_la_punta_de_la_sombra() {
+    let flatlanders = vec![Flatlander::nuevo(10.0, 10.0)];
+    let union = vec![Intervalo::nuevo(0.0, 10.0)];
+    let opciones = OpcionesDeSvg {
+        ancho: 140.0,
+        alto: 130.0,
+        margen: 20.0,
+        ..OpcionesDeSvg::default()
+    };
+    // con 135 grados la sombra va hacia la izquierda, de 10 a 0
+    let svg = escena_a_svg(&flatlanders, 135.0, &union, &opciones);
+    assert!(svg.contains("<line x1=\"120.00\" y1=\"20.00\" x2=\"20.00\" y2=\"90.00\"/>"));
+}
+
+#[test]
+fn escena_vacia_no_se_rompe() {
+    let svg = escena_a_svg(&[], 45.0, &[], &OpcionesDeSvg::default());
+    assert!(svg.ends_with("</svg>\n"));
+    assert!(!svg.contains("NaN") && !svg.contains("inf") && !svg.contains("-0"));
+}
+
+#[test]
+fn los_nombres_se_escapan() {
+    use crate::flatlander::Identidad;
+    let flatlander = Flatlander::nuevo(0.0, 1.0).con_identidad(Identidad {
+        nombre: Some("<a&b>".to_string()),
+        ..Identidad::default()
+    });
+    let union = vec![Intervalo::nuevo(0.0, 1.0)];
+    let svg = escena_a_svg(&[flatlander], 45.0, &union, &OpcionesDeSvg::default());
+    assert!(svg.contains("<title>&lt;a&amp;b&gt; h=1</title>"));
+}
+
+#[test]
+fn marcas_sin_arrastrar_decimales() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 1.0)];
+    let union = vec![Intervalo::nuevo(0.0, 1.0)];
+    // 3 * 0.2 da 0.6000000000000001
+    let svg = escena_a_svg(&flatlanders, 45.0, &union, &OpcionesDeSvg::default());
+    assert!(svg.contains(">0.6</text>"));
+    assert!(!svg.contains("0000000"));
+}
+
+#[test]
+fn pasos_redondos() {
+    assert_eq!(paso_de_marcas(15.0), 2.0);
+    assert_eq!(paso_de_marcas(300_000.0), 50_000.0);
+    assert_eq!(paso_de_marcas(0.8), 0.1);
+}
@@ -0,0 +1,332 @@
+/// Archivo para dibujar una escena como texto, para mirarla rapido en una terminal
+use crate::barrido::rango_de_sombras;
+use crate::cobertura::TramoDeProfundidad;
+use crate::cobertura_por_angulo::cobertura_por_angulo;
+use crate::flatlander::Flatlander;
+use crate::intervalo::Intervalo;
+use crate::longitud_union::longitud_total;
+use crate::numeros::FormatoDeNumeros;
+use std::fmt::Write;
+
+/// Ancho que se usa cuando no se sabe el de la terminal
+pub const ANCHO_POR_DEFECTO: usize = 80;
+
+/// Con que caracteres se dibuja
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JuegoDeCaracteres {
+    /// Solo ASCII, anda en cualquier terminal: la profundidad va en digitos
+    #[default]
+    Ascii,
+    /// Bloques de Unicode: la profundidad va en niveles de sombreado
+    Unicode,
+}
+
+impl JuegoDeCaracteres {
+    fn flatlander(self) -> char {
+        match self {
+            JuegoDeCaracteres::Ascii => '|',
+            JuegoDeCaracteres::Unicode => '┃',
+        }
+    }
+
+    /// Caracter de la sombra, segun si cubre toda la celda o solo una parte
+    fn sombra(self, completa: bool) -> char {
+        match (self, completa) {
+            (JuegoDeCaracteres::Ascii, true) => '#',
+            (JuegoDeCaracteres::Ascii, false) => ':',
+            (JuegoDeCaracteres::Unicode, true) => '█',
+            (JuegoDeCaracteres::Unicode, false) => '░',
+        }
+    }
+
+    /// Caracter de la profundidad, relativa a la maxima en el sombreado
+    fn profundidad(self, profundidad: usize, maxima: usize) -> char {
+        const NIVELES: [char; 4] = ['░', '▒', '▓', '█'];
+        match self {
+            _ if profundidad == 0 => ' ',
+            JuegoDeCaracteres::Ascii => char::from_digit(profundidad as u32, 10).unwrap_or('+'),
+            JuegoDeCaracteres::Unicode => {
+                let nivel = (profundidad * NIVELES.len()).div_ceil(maxima.max(1));
+                NIVELES[nivel.clamp(1, NIVELES.len()) - 1]
+            }
+        }
+    }
+
+    /// Niveles de la curva, del mas bajo al mas alto
+    fn niveles_de_curva(self) -> &'static [char] {
+        match self {
+            JuegoDeCaracteres::Ascii => &['_', '.', '-', '=', '+', '*', '#', '@'],
+            JuegoDeCaracteres::Unicode => &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+        }
+    }
+}
+
+/// Tamaño y caracteres del dibujo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpcionesDeTerminal {
+    /// Columnas del dibujo
+    pub ancho: usize,
+    /// Filas que ocupa el flatlander mas alto
+    pub alto: usize,
+    /// Con que caracteres se dibuja
+    pub juego: JuegoDeCaracteres,
+    /// Como se escriben los numeros del eje y del total
+    pub numeros: FormatoDeNumeros,
+}
+
+impl Default for OpcionesDeTerminal {
+    fn default() -> Self {
+        Self {
+            ancho: ANCHO_POR_DEFECTO,
+            alto: 8,
+            juego: JuegoDeCaracteres::default(),
+            numeros: FormatoDeNumeros::default(),
+        }
+    }
+}
+
+/// Dibuja la escena escalada al ancho pedido
+///
+/// Arriba van los flatlanders como barras, despues una fila con la union de las sombras, otra con
+/// la profundidad de cobertura y al final el eje con los extremos y el total. Cada columna es un
+/// tramo del piso: la sombra se marca completa si cubre al menos la mitad del tramo, y la
+/// profundidad es la mayor que se da adentro del tramo
+pub fn escena_a_texto(
+    flatlanders: &[Flatlander],
+    angulo: f64,
+    union: &[Intervalo],
+    perfil: &[TramoDeProfundidad],
+    opciones: &OpcionesDeTerminal,
+) -> String {
+    let ancho = opciones.ancho.max(1);
+    let juego = opciones.juego;
+    let (x_min, x_max) = rango_de_sombras(flatlanders, &[angulo]);
+    let celda = (x_max - x_min) / ancho as f64;
+    let columna = |x: f64| (((x - x_min) / celda) as usize).min(ancho - 1);
+
+    let mut alturas = vec![0.0_f64; ancho];
+    for flatlander in flatlanders {
+        let c = columna(flatlander.x);
+        alturas[c] = alturas[c].max(flatlander.h);
+    }
+    let h_max = alturas.iter().copied().fold(0.0, f64::max);
+    let filas = opciones.alto.max(1);
+
+    let mut texto = String::new();
+    for fila in (1..=filas).rev() {
+        let piso_de_la_fila = (fila - 1) as f64 / filas as f64 * h_max;
+        let linea: String = alturas
+            .iter()
+            .map(|&h| {
+                if h > 0.0 && h > piso_de_la_fila {
+                    juego.flatlander()
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        texto.push_str(linea.trim_end());
+        texto.push('\n');
+    }
+
+    let cubierto = cubierto_por_celda(union, x_min, celda, ancho);
+    let sombra: String = cubierto
+        .iter()
+        .map(|&parte| match parte {
+            p if p >= 0.5 => juego.sombra(true),
+            p if p > 0.0 => juego.sombra(false),
+            _ => ' ',
+        })
+        .collect();
+    let _ = writeln!(texto, "{}", sombra.trim_end());
+
+    let profundidades = profundidad_por_celda(perfil, x_min, celda, ancho);
+    let maxima = profundidades.iter().copied().max().unwrap_or(0);
+    let profundidad: String = profundidades
+        .iter()
+        .map(|&p| juego.profundidad(p, maxima))
+        .collect();
+    let _ = writeln!(texto, "{}", profundidad.trim_end());
+
+    let numeros = &opciones.numeros;
+    let izquierda = numeros.formatear(x_min);
+    let derecha = numeros.formatear(x_max);
+    let espacio = ancho.saturating_sub(izquierda.chars().count() + derecha.chars().count());
+    let total = longitud_total(union);
+    let _ = writeln!(
+        texto,
+        "{izquierda}{}{derecha}  total {}",
+        " ".repeat(espacio.max(1)),
+        numeros.formatear(total)
+    );
+    texto
+}
+
+/// Funcion interna que calcula que parte de cada celda cubren los intervalos, que estan ordenados
+fn cubierto_por_celda(intervalos: &[Intervalo], x_min: f64, celda: f64, ancho: usize) -> Vec<f64> {
+    let mut cubierto = vec![0.0; ancho];
+    let mut primero = 0;
+    for (c, parte) in cubierto.iter_mut().enumerate() {
+        let (desde, hasta) = (x_min + c as f64 * celda, x_min + (c + 1) as f64 * celda);
+        while primero < intervalos.len() && intervalos[primero].fin <= desde {
+            primero += 1;
+        }
+        let largo: f64 = intervalos[primero..]
+            .iter()
+            .take_while(|intervalo| intervalo.inicio < hasta)
+            .map(|intervalo| intervalo.fin.min(hasta) - intervalo.inicio.max(desde))
+            .sum();
+        *parte = largo / celda;
+    }
+    cubierto
+}
+
+/// Funcion interna que busca la mayor profundidad dentro de cada celda, con el perfil ordenado
+fn profundidad_por_celda(
+    perfil: &[TramoDeProfundidad],
+    x_min: f64,
+    celda: f64,
+    ancho: usize,
+) -> Vec<usize> {
+    let mut profundidades = vec![0; ancho];
+    let mut primero = 0;
+    for (c, profundidad) in profundidades.iter_mut().enumerate() {
+        let (desde, hasta) = (x_min + c as f64 * celda, x_min + (c + 1) as f64 * celda);
+        while primero < perfil.len() && perfil[primero].0.fin <= desde {
+            primero += 1;
+        }
+        *profundidad = perfil[primero..]
+            .iter()
+            .take_while(|(tramo, _)| tramo.inicio < hasta)
+            .map(|(_, p)| *p)
+            .max()
+            .unwrap_or(0);
+    }
+    profundidades
+}
+
+/// Convierte los valores en una linea de caracteres de alturas distintas, escalados entre el
+/// minimo y el maximo
+pub fn linea_de_chispas(valores: &[f64], juego: JuegoDeCaracteres) -> String {
+    let niveles = juego.niveles_de_curva();
+    let minimo = valores.iter().copied().fold(f64::INFINITY, f64::min);
+    let maximo = valores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let rango = maximo - minimo;
+    valores
+        .iter()
+        .map(|&valor| {
+            let nivel = if rango > 0.0 {
+                ((valor - minimo) / rango * (niveles.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            niveles[nivel.min(niveles.len() - 1)]
+        })
+        .collect()
+}
+
+/// Curva de la longitud de la union en funcion del angulo, con ancho puntos entre desde y hasta
+///
+/// Usa la cobertura exacta por angulo, asi que no hace falta recalcular la union en cada punto.
+/// Devuelve None si los angulos no sirven, con las mismas reglas que cobertura_por_angulo
+pub fn curva_de_cobertura(
+    flatlanders: &[Flatlander],
+    desde: f64,
+    hasta: f64,
+    ancho: usize,
+    juego: JuegoDeCaracteres,
+) -> Option<String> {
+    let cobertura = cobertura_por_angulo(flatlanders, desde, hasta)?;
+    let puntos = ancho.max(2);
+    let paso = (hasta - desde) / (puntos - 1) as f64;
+    let valores: Vec<f64> = (0..puntos)
+        .map(|i| cobertura.evaluar(desde + i as f64 * paso).unwrap_or(0.0))
+        .collect();
+    Some(linea_de_chispas(&valores, juego))
+}
+
+#[cfg(test)]
+fn dibujar(flatlanders: &[Flatlander], angulo: f64, opciones: &OpcionesDeTerminal) -> String {
+    use crate::cobertura::perfil_de_profundidad;
+    use crate::longitud_union::{sombras_desde_flatlanders, union_de_intervalos};
+    let sombras = sombras_desde_flatlanders(flatlanders, angulo);
+    let perfil = perfil_de_profundidad(&sombras);
+    let union = union_de_intervalos(sombras);
+    escena_a_texto(flatlanders, angulo, &union, &perfil, opciones)
+}
+
+#[test]
+fn dibuja_barras_sombra_y_profundidad() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(4.5, 5.0)];
+    let opciones = OpcionesDeTerminal {
+        ancho: 10,
+        alto: 2,
+        numeros: FormatoDeNumeros {
+            decimales: 1,
+            ..FormatoDeNumeros::default()
+        },
+        ..OpcionesDeTerminal::default()
+    };
+    // cada columna mide 1: las sombras son [0, 10] y [4.5, 9.5]
+    assert_eq!(
+        dibujar(&flatlanders, 45.0, &opciones),
+        "|\n|   |\n##########\n1111222222\n0.0   10.0  total 10.0\n"
+    );
+}
+
+#[test]
+fn con_unicode_la_profundidad_es_sombreado() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(4.5, 5.0)];
+    let opciones = OpcionesDeTerminal {
+        ancho: 10,
+        alto: 1,
+        juego: JuegoDeCaracteres::Unicode,
+        ..OpcionesDeTerminal::default()
+    };
+    let texto = dibujar(&flatlanders, 45.0, &opciones);
+    let lineas: Vec<&str> = texto.lines().collect();
+    assert_eq!(lineas[0], "┃   ┃");
+    assert_eq!(lineas[1], "██████████");
+    assert_eq!(lineas[2], "▒▒▒▒██████");
+}
+
+#[test]
+fn sombra_parcial_y_huecos() {
+    let union = vec![Intervalo::nuevo(0.0, 1.0), Intervalo::nuevo(2.2, 4.0)];
+    let cubierto = cubierto_por_celda(&union, 0.0, 1.0, 4);
+    assert_eq!(cubierto.len(), 4);
+    assert!((cubierto[0] - 1.0).abs() < 1e-12);
+    assert_eq!(cubierto[1], 0.0);
+    assert!((cubierto[2] - 0.8).abs() < 1e-12);
+}
+
+#[test]
+fn escena_vacia_no_se_rompe() {
+    let texto = dibujar(&[], 45.0, &OpcionesDeTerminal::default());
+    assert!(texto.ends_with("total 0.0000000000000\n"));
+}
+
+#[test]
+fn chispas_van_del_minimo_al_maximo() {
+    assert_eq!(
+        linea_de_chispas(&[0.0, 7.0, 3.5, 1.0], JuegoDeCaracteres::Ascii),
+        "_@+."
+    );
+    assert_eq!(
+        linea_de_chispas(&[2.0, 2.0], JuegoDeCaracteres::Unicode),
+        "▁▁"
+    );
+}
+
+#[test]
+fn la_curva_baja_con_el_angulo() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0)];
+    let curva = curva_de_cobertura(&flatlanders, 10.0, 80.0, 5, JuegoDeCaracteres::Ascii).unwrap();
+    assert_eq!(curva.chars().count(), 5);
+    assert!(curva.starts_with('@'));
+    assert!(curva.ends_with('_'));
+    assert_eq!(
+        curva_de_cobertura(&flatlanders, 80.0, 10.0, 5, JuegoDeCaracteres::Ascii),
+        None
+    );
+}
@@ -0,0 +1,304 @@
+/// Archivo para parsear el formato anotado, la version 2 de la entrada con comentarios y nombres
+use crate::flatlander::{Flatlander, Identidad};
+use crate::parseador::{
+    ErrorParseo, Limites, ModoDeParseo, OpcionesDeParseo, Token, Tokens, crear_flatlander,
+    parsear_angulo, validar_cantidad,
+};
+
+/// Primera linea que marca que la entrada esta en el formato anotado
+pub const MARCA_V2: &str = "#v2";
+
+/// Escena leida del formato anotado, con los datos que el formato clasico no tiene
+#[derive(Debug, PartialEq)]
+pub struct EscenaAnotada {
+    /// Nombre de la escena, de la directiva nombre
+    pub nombre: Option<String>,
+    /// Unidades de x y h, de la directiva unidades, solo informativas
+    pub unidades: Option<String>,
+    /// Angulo del sol, de la directiva angulo
+    pub angulo: f64,
+    /// Flatlanders en el orden de la entrada, con su identidad si la tenian
+    pub flatlanders: Vec<Flatlander>,
+}
+
+/// Dice si la entrada esta en el formato anotado mirando si empieza con la marca
+///
+/// Recibe bytes para poder mirar el principio de un lector sin consumirlo
+pub fn es_escena_anotada(entrada: &[u8]) -> bool {
+    match entrada.strip_prefix(MARCA_V2.as_bytes()) {
+        Some(resto) => resto.first().is_none_or(|b| b.is_ascii_whitespace()),
+        None => false,
+    }
+}
+
+/// Parsea una escena en el formato anotado
+///
+/// ```text
+/// #v2
+/// # todo lo que sigue a un numeral es un comentario
+/// name=Plaza central
+/// angle=45
+/// units=m
+/// 0 10 ana norte edad=30
+/// 5 10 - sur
+/// 12 3
+/// ```
+///
+/// La primera linea es la marca. Antes del primer flatlander van las directivas clave=valor, una
+/// por linea, donde el valor es el resto de la linea; angle es obligatoria y name y units son
+/// opcionales, y tambien se aceptan en espanol como angulo, nombre y unidades. Cada flatlander
+/// tiene x y h y despues, si se quiere, su nombre, su grupo (con - se deja sin nombre o sin
+/// grupo) y atributos clave=valor
+///
+/// No hay n: los flatlanders siguen hasta el final, y la cantidad tiene que estar entre n_min y
+/// n_max o se devuelve fuera de rango en la linea que sigue a la ultima
+pub fn parsear_escena_anotada(
+    entrada: &str,
+    limites: Option<&Limites>,
+) -> Result<EscenaAnotada, ErrorParseo> {
+    parsear_escena_anotada_con_opciones(entrada, limites, OpcionesDeParseo::default())
+}
+
+/// Como parsear_escena_anotada pero con opciones de parseo, el modo no cambia nada en este formato
+pub fn parsear_escena_anotada_con_opciones(
+    entrada: &str,
+    limites: Option<&Limites>,
+    opciones: OpcionesDeParseo,
+) -> Result<EscenaAnotada, ErrorParseo> {
+    if !es_escena_anotada(entrada.as_bytes()) {
+        return Err(ErrorParseo::DirectivaFaltante {
+            linea: 1,
+            clave: MARCA_V2.to_string(),
+        });
+    }
+    let limites = limites.copied().unwrap_or_default();
+    let opciones = OpcionesDeParseo {
+        modo: ModoDeParseo::Normal,
+        ..opciones
+    };
+    let mut escena = EscenaAnotada {
+        nombre: None,
+        unidades: None,
+        angulo: 0.0,
+        flatlanders: Vec::new(),
+    };
+    let mut angulo = None;
+    let mut ultima_linea = 1;
+
+    for (i, linea) in entrada.lines().enumerate().skip(1) {
+        let numero_linea = i + 1;
+        ultima_linea = numero_linea;
+        let linea = sin_comentario(linea);
+        let mut tokens = Tokens::nuevo(linea, numero_linea);
+        let Some(primero) = tokens.siguiente_opcional() else {
+            continue;
+        };
+        if let Some((clave, _)) = primero.texto.split_once('=') {
+            if !escena.flatlanders.is_empty() {
+                return Err(directiva_invalida(&primero));
+            }
+            // el valor es el resto de la linea, asi el nombre de la escena puede tener espacios
+            let desplazamiento = primero.texto.as_ptr() as usize - linea.as_ptr() as usize;
+            let valor = linea[desplazamiento + clave.len() + 1..].trim();
+            if valor.is_empty() {
+                return Err(directiva_invalida(&primero));
+            }
+            match clave_de_directiva(clave) {
+                "angulo" if angulo.is_none() => {
+                    angulo = Some(parsear_angulo(&tokens.token(valor), &limites, &opciones)?);
+                }
+                "nombre" if escena.nombre.is_none() => escena.nombre = Some(valor.to_string()),
+                "unidades" if escena.unidades.is_none() => {
+                    escena.unidades = Some(valor.to_string())
+                }
+                _ => return Err(directiva_invalida(&primero)),
+            }
+            continue;
+        }
+        if angulo.is_none() {
+            return Err(ErrorParseo::DirectivaFaltante {
+                linea: numero_linea,
+                clave: "angulo".to_string(),
+            });
+        }
+        let mut tokens = Tokens::nuevo(linea, numero_linea);
+        let flatlander = crear_flatlander(&mut tokens, &limites, &opciones)?;
+        escena
+            .flatlanders
+            .push(match crear_identidad(&mut tokens)? {
+                Some(identidad) => flatlander.con_identidad(identidad),
+                None => flatlander,
+            });
+    }
+
+    escena.angulo = angulo.ok_or(ErrorParseo::DirectivaFaltante {
+        linea: ultima_linea + 1,
+        clave: "angulo".to_string(),
+    })?;
+    validar_cantidad(escena.flatlanders.len(), &limites, ultima_linea + 1, 1)?;
+    Ok(escena)
+}
+
+/// Funcion interna que pasa las claves en ingles a las de siempre, las demas quedan igual
+fn clave_de_directiva(clave: &str) -> &str {
+    match clave {
+        "angle" => "angulo",
+        "name" => "nombre",
+        "units" => "unidades",
+        otra => otra,
+    }
+}
+
+/// Funcion interna que saca el comentario del final de la linea
+fn sin_comentario(linea: &str) -> &str {
+    linea.split_once('#').map_or(linea, |(antes, _)| antes)
+}
+
+/// Funcion interna que arma el error de directiva invalida con el token de la clave
+fn directiva_invalida(token: &Token<'_>) -> ErrorParseo {
+    ErrorParseo::DirectivaInvalida {
+        linea: token.linea,
+        columna: token.columna,
+        token: token.texto.to_string(),
+    }
+}
+
+/// Funcion interna que lee las columnas opcionales que siguen a x y h
+///
+/// Devuelve None si no habia ninguna, y datos sobrantes si hay un tercer valor suelto o un
+/// atributo sin clave
+fn crear_identidad(tokens: &mut Tokens<'_>) -> Result<Option<Identidad>, ErrorParseo> {
+    let mut identidad = Identidad::default();
+    let mut sueltos = 0;
+    while let Some(token) = tokens.siguiente_opcional() {
+        let sobrante = || ErrorParseo::DatosSobrantes {
+            linea: token.linea,
+            columna: token.columna,
+            token: token.texto.to_string(),
+        };
+        match token.texto.split_once('=') {
+            Some(("", _)) => return Err(sobrante()),
+            Some((clave, valor)) => identidad
+                .atributos
+                .push((clave.to_string(), valor.to_string())),
+            None => {
+                let valor = (token.texto != "-").then(|| token.texto.to_string());
+                match sueltos {
+                    0 => identidad.nombre = valor,
+                    1 => identidad.grupo = valor,
+                    _ => return Err(sobrante()),
+                }
+                sueltos += 1;
+            }
+        }
+    }
+    Ok((identidad != Identidad::default()).then_some(identidad))
+}
+
+#[test]
+fn parsea_escena_anotada_completa() {
+    let entrada = "#v2\n# comentario\nnombre=Plaza central\nangulo=45 # al mediodia\n\
+                   unidades=m\n\n0 10 ana norte edad=30\n5 10 - sur\n12 3\n";
+    let escena = parsear_escena_anotada(entrada, None).unwrap();
+    assert_eq!(escena.nombre.as_deref(), Some("Plaza central"));
+    assert_eq!(escena.unidades.as_deref(), Some("m"));
+    assert_eq!(escena.angulo, 45.0);
+    assert_eq!(escena.flatlanders.len(), 3);
+    assert_eq!(escena.flatlanders[0].nombre(), Some("ana"));
+    assert_eq!(escena.flatlanders[0].grupo(), Some("norte"));
+    assert_eq!(
+        escena.flatlanders[0].identidad.as_ref().unwrap().atributos,
+        vec![("edad".to_string(), "30".to_string())]
+    );
+    assert_eq!(escena.flatlanders[1].nombre(), None);
+    assert_eq!(escena.flatlanders[1].grupo(), Some("sur"));
+    assert_eq!(escena.flatlanders[2], Flatlander::nuevo(12.0, 3.0));
+}
+
+#[test]
+fn acepta_las_directivas_en_ingles() {
+    let entrada = "#v2\nname=Plaza central\nangle=45\nunits=m\n0 10\n";
+    let escena = parsear_escena_anotada(entrada, None).unwrap();
+    assert_eq!(escena.nombre.as_deref(), Some("Plaza central"));
+    assert_eq!(escena.unidades.as_deref(), Some("m"));
+    assert_eq!(escena.angulo, 45.0);
+    assert_eq!(
+        parsear_escena_anotada("#v2\nangle=45\n0 10\n", None),
+        parsear_escena_anotada("#v2\nangulo=45\n0 10\n", None)
+    );
+    let repetida = parsear_escena_anotada("#v2\nangle=45\nangulo=30\n0 1\n", None);
+    assert!(matches!(
+        repetida,
+        Err(ErrorParseo::DirectivaInvalida { linea: 3, .. })
+    ));
+}
+
+#[test]
+fn detecta_la_marca() {
+    assert!(es_escena_anotada(b"#v2\n"));
+    assert!(es_escena_anotada(b"#v2"));
+    assert!(!es_escena_anotada(b"#v20\n"));
+    assert!(!es_escena_anotada(b"45 3\n"));
+}
+
+#[test]
+fn falta_el_angulo() {
+    let salida = parsear_escena_anotada("#v2\nnombre=x\n0 10\n", None);
+    assert_eq!(
+        salida,
+        Err(ErrorParseo::DirectivaFaltante {
+            linea: 3,
+            clave: "angulo".to_string(),
+        })
+    );
+    let vacia = parsear_escena_anotada("#v2\n", None);
+    assert!(matches!(
+        vacia,
+        Err(ErrorParseo::DirectivaFaltante { linea: 2, .. })
+    ));
+}
+
+#[test]
+fn directivas_invalidas() {
+    let desconocida = parsear_escena_anotada("#v2\n  color=rojo\n", None);
+    assert_eq!(
+        desconocida,
+        Err(ErrorParseo::DirectivaInvalida {
+            linea: 2,
+            columna: 3,
+            token: "color=rojo".to_string(),
+        })
+    );
+    let repetida = parsear_escena_anotada("#v2\nangulo=45\nangulo=30\n0 1\n", None);
+    assert!(matches!(
+        repetida,
+        Err(ErrorParseo::DirectivaInvalida { linea: 3, .. })
+    ));
+    let tarde = parsear_escena_anotada("#v2\nangulo=45\n0 1\nnombre=x\n", None);
+    assert!(matches!(
+        tarde,
+        Err(ErrorParseo::DirectivaInvalida { linea: 4, .. })
+    ));
+}
+
+#[test]
+fn angulo_de_directiva_se_valida() {
+    let salida = parsear_escena_anotada("#v2\nangulo= 85\n0 1\n", None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 2,
+            columna: 9,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn sobra_una_columna_suelta() {
+    let salida = parsear_escena_anotada("#v2\nangulo=45\n0 1 ana norte extra\n", None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::DatosSobrantes { linea: 3, .. })
+    ));
+}
@@ -1,18 +1,60 @@
 /// Representa un flatlander
 ///
-/// Cada flatlander tiene su posicion x y su altura h
+/// Cada flatlander tiene su posicion x y su altura h, y si vino del formato anotado tambien
+/// puede tener una identidad
 #[derive(Debug, PartialEq)]
 pub struct Flatlander {
     /// Posicion x
     pub x: f64,
     /// Altura
     pub h: f64,
+    /// Nombre, grupo y atributos, en una caja para que el flatlander siga siendo chico al ordenar
+    pub identidad: Option<Box<Identidad>>,
+}
+
+/// Datos opcionales del formato anotado para poder nombrar a un flatlander en los resultados
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Identidad {
+    /// Nombre o id del flatlander
+    pub nombre: Option<String>,
+    /// Grupo al que pertenece
+    pub grupo: Option<String>,
+    /// Atributos clave=valor en el orden de la entrada
+    pub atributos: Vec<(String, String)>,
 }
 
 impl Flatlander {
     /// Crea el flatlander con los valores dados y lo devuelve
     pub fn nuevo(x: f64, h: f64) -> Self {
-        Self { x, h }
+        Self {
+            x,
+            h,
+            identidad: None,
+        }
+    }
+
+    /// Devuelve el mismo flatlander con la identidad dada
+    pub fn con_identidad(mut self, identidad: Identidad) -> Self {
+        self.identidad = Some(Box::new(identidad));
+        self
+    }
+
+    /// Nombre del flatlander, si tiene
+    pub fn nombre(&self) -> Option<&str> {
+        self.identidad.as_ref()?.nombre.as_deref()
+    }
+
+    /// Grupo del flatlander, si tiene
+    pub fn grupo(&self) -> Option<&str> {
+        self.identidad.as_ref()?.grupo.as_deref()
+    }
+
+    /// Como mostrar al flatlander en los resultados: su nombre o, si no tiene, su numero empezando en 1
+    pub fn etiqueta(&self, indice: usize) -> String {
+        match self.nombre() {
+            Some(nombre) => nombre.to_string(),
+            None => (indice + 1).to_string(),
+        }
     }
 }
 
@@ -21,4 +63,17 @@ fn crea_flatlander() {
     let flatlander = Flatlander::nuevo(10.0, 5.0);
     assert_eq!(flatlander.x, 10.0);
     assert_eq!(flatlander.h, 5.0);
+    assert_eq!(flatlander.identidad, None);
+}
+
+#[test]
+fn etiqueta_con_y_sin_nombre() {
+    let anonimo = Flatlander::nuevo(0.0, 1.0);
+    assert_eq!(anonimo.etiqueta(2), "3");
+    let nombrado = Flatlander::nuevo(0.0, 1.0).con_identidad(Identidad {
+        nombre: Some("ana".to_string()),
+        ..Identidad::default()
+    });
+    assert_eq!(nombrado.etiqueta(2), "ana");
+    assert_eq!(nombrado.grupo(), None);
 }
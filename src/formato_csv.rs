@@ -0,0 +1,340 @@
+/// Archivo para leer y escribir escenas y resultados en CSV
+use crate::flatlander::{Flatlander, Identidad};
+use crate::formatos::ResumenDeEscena;
+use crate::numeros::{FormatoDeNumeros, SeparadorDecimal};
+use crate::parseador::{
+    ErrorParseo, Limites, OpcionesDeParseo, Token, parsear_token_f64, validar_cantidad,
+    validar_rango,
+};
+
+/// Campo de una fila ya sin comillas, con la columna donde empieza
+struct Campo {
+    texto: String,
+    columna: usize,
+}
+
+/// Parsea los flatlanders de un CSV con fila de encabezado
+///
+/// El encabezado tiene que tener las columnas x y h, en cualquier orden y sin importar
+/// mayusculas. Las columnas nombre y grupo van a la identidad y las demas quedan como atributos.
+/// Los campos pueden ir entre comillas dobles, con "" para una comilla, pero no pueden tener
+/// saltos de linea
+///
+/// Las lineas en blanco se ignoran y la cantidad de filas tiene que estar entre n_min y n_max
+pub fn parsear_csv(
+    entrada: &str,
+    limites: Option<&Limites>,
+) -> Result<Vec<Flatlander>, ErrorParseo> {
+    parsear_csv_con_opciones(entrada, limites, OpcionesDeParseo::default())
+}
+
+/// Como parsear_csv pero con opciones de parseo
+///
+/// Con coma decimal los campos se separan con punto y coma, como en las planillas en castellano
+pub fn parsear_csv_con_opciones(
+    entrada: &str,
+    limites: Option<&Limites>,
+    opciones: OpcionesDeParseo,
+) -> Result<Vec<Flatlander>, ErrorParseo> {
+    let limites = limites.copied().unwrap_or_default();
+    let separador = separador_de_campos(opciones.decimal);
+    let entrada = entrada.strip_prefix('\u{feff}').unwrap_or(entrada);
+    let mut filas = entrada
+        .lines()
+        .enumerate()
+        .map(|(i, linea)| (i + 1, linea))
+        .filter(|(_, linea)| !linea.trim().is_empty());
+
+    let Some((linea_encabezado, encabezado)) = filas.next() else {
+        return Err(ErrorParseo::LineaFaltante { linea: 1 });
+    };
+    let columnas: Vec<String> = separar_campos(encabezado, linea_encabezado, separador)?
+        .into_iter()
+        .map(|campo| campo.texto.trim().to_ascii_lowercase())
+        .collect();
+    let posicion = |nombre: &str| columnas.iter().position(|c| c == nombre);
+    let (Some(columna_x), Some(columna_h)) = (posicion("x"), posicion("h")) else {
+        return Err(ErrorParseo::ValorFaltante {
+            linea: linea_encabezado,
+            columna: encabezado.chars().count() + 1,
+        });
+    };
+
+    let mut flatlanders = Vec::new();
+    let mut ultima_linea = linea_encabezado;
+    for (numero_linea, linea) in filas {
+        ultima_linea = numero_linea;
+        let campos = separar_campos(linea, numero_linea, separador)?;
+        let numero = |indice: usize, minimo: f64, maximo: f64| {
+            let campo = campos.get(indice).ok_or(ErrorParseo::ValorFaltante {
+                linea: numero_linea,
+                columna: linea.chars().count() + 1,
+            })?;
+            let token = Token {
+                texto: campo.texto.trim(),
+                linea: numero_linea,
+                columna: campo.columna,
+            };
+            let valor = parsear_token_f64(&token, &opciones)?;
+            validar_rango(valor, minimo, maximo, &token)?;
+            Ok::<f64, ErrorParseo>(valor)
+        };
+        let x = numero(columna_x, limites.x_min, limites.x_max)?;
+        let h = numero(columna_h, limites.h_min, limites.h_max)?;
+
+        let mut identidad = Identidad::default();
+        for (indice, campo) in campos.iter().enumerate() {
+            let valor = campo.texto.trim();
+            if indice == columna_x || indice == columna_h || valor.is_empty() {
+                continue;
+            }
+            match columnas.get(indice).map(String::as_str) {
+                Some("nombre") => identidad.nombre = Some(valor.to_string()),
+                Some("grupo") => identidad.grupo = Some(valor.to_string()),
+                Some(clave) => identidad
+                    .atributos
+                    .push((clave.to_string(), valor.to_string())),
+                None => {
+                    return Err(ErrorParseo::DatosSobrantes {
+                        linea: numero_linea,
+                        columna: campo.columna,
+                        token: valor.to_string(),
+                    });
+                }
+            }
+        }
+        let flatlander = Flatlander::nuevo(x, h);
+        flatlanders.push(if identidad == Identidad::default() {
+            flatlander
+        } else {
+            flatlander.con_identidad(identidad)
+        });
+    }
+    validar_cantidad(flatlanders.len(), &limites, ultima_linea + 1, 1)?;
+    Ok(flatlanders)
+}
+
+/// Funcion interna que elige el separador de campos, que no puede ser el decimal
+fn separador_de_campos(decimal: SeparadorDecimal) -> char {
+    match decimal {
+        SeparadorDecimal::Punto => ',',
+        SeparadorDecimal::Coma => ';',
+    }
+}
+
+/// Funcion interna que separa una fila en campos sacando las comillas
+fn separar_campos(
+    linea: &str,
+    numero_linea: usize,
+    separador: char,
+) -> Result<Vec<Campo>, ErrorParseo> {
+    let mut campos = Vec::new();
+    let mut caracteres = linea.chars().enumerate().peekable();
+    loop {
+        let columna = caracteres.peek().map_or(linea.chars().count(), |(i, _)| *i) + 1;
+        let mut texto = String::new();
+        let mut entre_comillas = false;
+        let mut termino_la_fila = true;
+        while let Some((i, c)) = caracteres.next() {
+            match c {
+                '"' if entre_comillas => {
+                    if caracteres.next_if(|(_, c)| *c == '"').is_some() {
+                        texto.push('"');
+                    } else {
+                        entre_comillas = false;
+                    }
+                }
+                '"' if texto.trim().is_empty() => {
+                    texto.clear();
+                    entre_comillas = true;
+                }
+                c if c == separador && !entre_comillas => {
+                    termino_la_fila = false;
+                    break;
+                }
+                '"' => {
+                    return Err(ErrorParseo::FormatoInvalido {
+                        linea: numero_linea,
+                        columna: i + 1,
+                        token: c.to_string(),
+                    });
+                }
+                _ => texto.push(c),
+            }
+        }
+        if entre_comillas {
+            return Err(ErrorParseo::FormatoInvalido {
+                linea: numero_linea,
+                columna: linea.chars().count() + 1,
+                token: String::new(),
+            });
+        }
+        campos.push(Campo { texto, columna });
+        if termino_la_fila {
+            return Ok(campos);
+        }
+    }
+}
+
+/// Funcion interna que pone comillas al campo si las necesita
+fn escapar_campo(texto: &str) -> String {
+    if texto.contains([',', ';', '"']) || texto.trim() != texto {
+        format!("\"{}\"", texto.replace('"', "\"\""))
+    } else {
+        texto.to_string()
+    }
+}
+
+/// Escribe los flatlanders como CSV, en el mismo formato que lee parsear_csv
+///
+/// Las columnas nombre y grupo y las de los atributos solo aparecen si algun flatlander las usa.
+/// Con coma decimal los numeros van con coma y los campos se separan con punto y coma
+pub fn escena_a_csv(flatlanders: &[Flatlander], decimal: SeparadorDecimal) -> String {
+    let separador = separador_de_campos(decimal).to_string();
+    let identidades = || flatlanders.iter().filter_map(|f| f.identidad.as_deref());
+    let con_nombre = identidades().any(|i| i.nombre.is_some());
+    let con_grupo = identidades().any(|i| i.grupo.is_some());
+    let mut claves: Vec<&str> = Vec::new();
+    for (clave, _) in identidades().flat_map(|i| &i.atributos) {
+        if !claves.contains(&clave.as_str()) {
+            claves.push(clave);
+        }
+    }
+
+    let mut encabezado = vec!["x", "h"];
+    if con_nombre {
+        encabezado.push("nombre");
+    }
+    if con_grupo {
+        encabezado.push("grupo");
+    }
+    encabezado.extend(&claves);
+    let mut salida: Vec<String> = vec![
+        encabezado
+            .iter()
+            .map(|c| escapar_campo(c))
+            .collect::<Vec<_>>()
+            .join(&separador),
+    ];
+
+    for flatlander in flatlanders {
+        let mut fila = vec![
+            decimal.localizar(flatlander.x.to_string()),
+            decimal.localizar(flatlander.h.to_string()),
+        ];
+        if con_nombre {
+            fila.push(escapar_campo(flatlander.nombre().unwrap_or("")));
+        }
+        if con_grupo {
+            fila.push(escapar_campo(flatlander.grupo().unwrap_or("")));
+        }
+        for clave in &claves {
+            let valor = flatlander
+                .identidad
+                .as_deref()
+                .and_then(|i| i.atributos.iter().find(|(c, _)| c == clave))
+                .map_or("", |(_, v)| v.as_str());
+            fila.push(escapar_campo(valor));
+        }
+        salida.push(fila.join(&separador));
+    }
+    salida.join("\n") + "\n"
+}
+
+/// Escribe el resumen como una fila de encabezado y una fila con el angulo, n y el total
+///
+/// El total va con los decimales del formato y el angulo tal cual vino
+pub fn resumen_a_csv(resumen: &ResumenDeEscena, formato: &FormatoDeNumeros) -> String {
+    let decimal = formato.separador;
+    let separador = separador_de_campos(decimal);
+    format!(
+        "angulo{separador}n{separador}total\n{}{separador}{}{separador}{}\n",
+        decimal.localizar(resumen.angulo.to_string()),
+        resumen.n,
+        formato.formatear(resumen.total)
+    )
+}
+
+#[test]
+fn parsea_csv_con_columnas_extra() {
+    let entrada = "H, X ,nombre,color\n10,0,ana,rojo\n\n\"10\",5,\"de la \"\"plaza\"\", sur\",\n";
+    let flatlanders = parsear_csv(entrada, None).unwrap();
+    assert_eq!(flatlanders.len(), 2);
+    assert_eq!((flatlanders[0].x, flatlanders[0].h), (0.0, 10.0));
+    assert_eq!(flatlanders[0].nombre(), Some("ana"));
+    assert_eq!(
+        flatlanders[0].identidad.as_ref().unwrap().atributos,
+        vec![("color".to_string(), "rojo".to_string())]
+    );
+    assert_eq!((flatlanders[1].x, flatlanders[1].h), (5.0, 10.0));
+    assert_eq!(flatlanders[1].nombre(), Some("de la \"plaza\", sur"));
+}
+
+#[test]
+fn errores_de_csv() {
+    assert_eq!(
+        parsear_csv("x,y\n1,2\n", None),
+        Err(ErrorParseo::ValorFaltante {
+            linea: 1,
+            columna: 4,
+        })
+    );
+    assert_eq!(
+        parsear_csv("x,h\n1,dos\n", None),
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 2,
+            columna: 3,
+            token: "dos".to_string(),
+        })
+    );
+    assert!(matches!(
+        parsear_csv("x,h\n1,\"2\n", None),
+        Err(ErrorParseo::FormatoInvalido { linea: 2, .. })
+    ));
+    assert!(matches!(
+        parsear_csv("x,h\n", None),
+        Err(ErrorParseo::FueraDeRango { linea: 2, .. })
+    ));
+}
+
+#[test]
+fn csv_ida_y_vuelta() {
+    let flatlanders = vec![
+        Flatlander::nuevo(0.5, 10.0).con_identidad(Identidad {
+            nombre: Some("a, b".to_string()),
+            grupo: None,
+            atributos: vec![("edad".to_string(), "3".to_string())],
+        }),
+        Flatlander::nuevo(7.0, 2.25),
+    ];
+    let csv = escena_a_csv(&flatlanders, SeparadorDecimal::Punto);
+    assert_eq!(csv, "x,h,nombre,edad\n0.5,10,\"a, b\",3\n7,2.25,,\n");
+    assert_eq!(parsear_csv(&csv, None), Ok(flatlanders));
+}
+
+#[test]
+fn csv_con_coma_decimal() {
+    let opciones = OpcionesDeParseo {
+        decimal: SeparadorDecimal::Coma,
+        ..OpcionesDeParseo::default()
+    };
+    let entrada = "x;h\n1.500;12,5\n0,25;3\n";
+    let flatlanders = parsear_csv_con_opciones(entrada, None, opciones).unwrap();
+    assert_eq!(
+        flatlanders,
+        vec![
+            Flatlander::nuevo(1500.0, 12.5),
+            Flatlander::nuevo(0.25, 3.0)
+        ]
+    );
+    let csv = escena_a_csv(&flatlanders, SeparadorDecimal::Coma);
+    assert_eq!(csv, "x;h\n1500;12,5\n0,25;3\n");
+    assert_eq!(
+        parsear_csv_con_opciones(&csv, None, opciones),
+        Ok(flatlanders)
+    );
+    assert!(matches!(
+        parsear_csv_con_opciones("x;h\n1;12.5\n", None, opciones),
+        Err(ErrorParseo::NumeroInvalido { columna: 3, .. })
+    ));
+}
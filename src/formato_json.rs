@@ -0,0 +1,569 @@
+/// Archivo para leer y escribir escenas y resultados en JSON, con un parseador chico propio
+use crate::flatlander::{Flatlander, Identidad};
+use crate::formatos::ResumenDeEscena;
+use crate::parseador::{
+    ErrorParseo, Limites, OpcionesDeParseo, Token, parsear_angulo, parsear_token_f64,
+    validar_cantidad, validar_rango,
+};
+use std::fmt::Write;
+
+/// Cuantas listas y objetos puede haber uno dentro de otro, para no llenar la pila con entradas raras
+const PROFUNDIDAD_MAXIMA: usize = 64;
+
+/// Valor de JSON junto con la posicion donde empieza, para poder armar los errores
+struct Valor<'a> {
+    tipo: Tipo<'a>,
+    linea: usize,
+    columna: usize,
+}
+
+/// Los tipos de JSON, los numeros quedan como texto para parsearlos igual que en los otros formatos
+enum Tipo<'a> {
+    Nulo,
+    Booleano(bool),
+    Numero(&'a str),
+    Texto(String),
+    Lista(Vec<Valor<'a>>),
+    Objeto(Vec<(String, Valor<'a>)>),
+}
+
+impl<'a> Valor<'a> {
+    /// Busca un campo del objeto, None si no es un objeto o no tiene el campo
+    fn campo(&self, clave: &str) -> Option<&Valor<'a>> {
+        match &self.tipo {
+            Tipo::Objeto(campos) => campos.iter().find(|(c, _)| c == clave).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Error de formato invalido en la posicion del valor
+    fn error(&self) -> ErrorParseo {
+        ErrorParseo::FormatoInvalido {
+            linea: self.linea,
+            columna: self.columna,
+            token: String::new(),
+        }
+    }
+
+    /// Parsea el valor como numero con sus limites, sirve para x y h
+    fn numero(&self, minimo: f64, maximo: f64) -> Result<f64, ErrorParseo> {
+        let token = self.token()?;
+        let valor = parsear_token_f64(&token, &OpcionesDeParseo::default())?;
+        validar_rango(valor, minimo, maximo, &token)?;
+        Ok(valor)
+    }
+
+    /// Arma el token de un numero para usar las validaciones del parseador
+    fn token(&self) -> Result<Token<'a>, ErrorParseo> {
+        match self.tipo {
+            Tipo::Numero(texto) => Ok(Token {
+                texto,
+                linea: self.linea,
+                columna: self.columna,
+            }),
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Texto de un valor simple para guardarlo como atributo
+    fn como_texto(&self) -> Result<String, ErrorParseo> {
+        match &self.tipo {
+            Tipo::Nulo => Ok("null".to_string()),
+            Tipo::Booleano(b) => Ok(b.to_string()),
+            Tipo::Numero(texto) => Ok(texto.to_string()),
+            Tipo::Texto(texto) => Ok(texto.clone()),
+            Tipo::Lista(_) | Tipo::Objeto(_) => Err(self.error()),
+        }
+    }
+}
+
+/// Recorre el texto de a un caracter llevando la linea y la columna
+struct LectorJson<'a> {
+    texto: &'a str,
+    posicion: usize,
+    linea: usize,
+    columna: usize,
+}
+
+impl<'a> LectorJson<'a> {
+    fn nuevo(texto: &'a str) -> Self {
+        Self {
+            texto,
+            posicion: 0,
+            linea: 1,
+            columna: 1,
+        }
+    }
+
+    fn mirar(&self) -> Option<char> {
+        self.texto[self.posicion..].chars().next()
+    }
+
+    fn avanzar(&mut self) -> Option<char> {
+        let c = self.mirar()?;
+        self.posicion += c.len_utf8();
+        if c == '\n' {
+            self.linea += 1;
+            self.columna = 1;
+        } else {
+            self.columna += 1;
+        }
+        Some(c)
+    }
+
+    fn saltear_espacios(&mut self) {
+        while self
+            .mirar()
+            .is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r'))
+        {
+            self.avanzar();
+        }
+    }
+
+    /// Error de formato invalido en la posicion actual, con el caracter que no se esperaba
+    fn error(&self) -> ErrorParseo {
+        ErrorParseo::FormatoInvalido {
+            linea: self.linea,
+            columna: self.columna,
+            token: self.mirar().map(String::from).unwrap_or_default(),
+        }
+    }
+
+    /// Avanza si el siguiente caracter es el esperado, si no devuelve el error
+    fn esperar(&mut self, esperado: char) -> Result<(), ErrorParseo> {
+        if self.mirar() == Some(esperado) {
+            self.avanzar();
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Lee el documento entero, que tiene que ser un solo valor
+    fn documento(&mut self) -> Result<Valor<'a>, ErrorParseo> {
+        if self.mirar() == Some('\u{feff}') {
+            self.posicion += '\u{feff}'.len_utf8();
+        }
+        let valor = self.valor(0)?;
+        self.saltear_espacios();
+        match self.mirar() {
+            None => Ok(valor),
+            Some(_) => Err(self.error()),
+        }
+    }
+
+    fn valor(&mut self, profundidad: usize) -> Result<Valor<'a>, ErrorParseo> {
+        self.saltear_espacios();
+        let (linea, columna) = (self.linea, self.columna);
+        let tipo = match self.mirar() {
+            Some('[' | '{') if profundidad >= PROFUNDIDAD_MAXIMA => return Err(self.error()),
+            Some('[') => Tipo::Lista(self.lista(profundidad)?),
+            Some('{') => Tipo::Objeto(self.objeto(profundidad)?),
+            Some('"') => Tipo::Texto(self.texto()?),
+            Some('-' | '0'..='9') => Tipo::Numero(self.numero()),
+            Some('t') => self.literal("true", Tipo::Booleano(true))?,
+            Some('f') => self.literal("false", Tipo::Booleano(false))?,
+            Some('n') => self.literal("null", Tipo::Nulo)?,
+            _ => return Err(self.error()),
+        };
+        Ok(Valor {
+            tipo,
+            linea,
+            columna,
+        })
+    }
+
+    fn literal(&mut self, palabra: &str, tipo: Tipo<'a>) -> Result<Tipo<'a>, ErrorParseo> {
+        if !self.texto[self.posicion..].starts_with(palabra) {
+            return Err(self.error());
+        }
+        for _ in 0..palabra.len() {
+            self.avanzar();
+        }
+        Ok(tipo)
+    }
+
+    /// Toma los caracteres que pueden ser de un numero, si no es valido falla despues al parsearlo
+    fn numero(&mut self) -> &'a str {
+        let inicio = self.posicion;
+        while self
+            .mirar()
+            .is_some_and(|c| matches!(c, '0'..='9' | '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.avanzar();
+        }
+        &self.texto[inicio..self.posicion]
+    }
+
+    fn texto(&mut self) -> Result<String, ErrorParseo> {
+        self.esperar('"')?;
+        let mut texto = String::new();
+        loop {
+            match self.mirar() {
+                Some('"') => {
+                    self.avanzar();
+                    return Ok(texto);
+                }
+                Some('\\') => {
+                    self.avanzar();
+                    let escapado = match self.mirar() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.avanzar();
+                            texto.push(self.unicode()?);
+                            continue;
+                        }
+                        _ => return Err(self.error()),
+                    };
+                    self.avanzar();
+                    texto.push(escapado);
+                }
+                Some(c) if c >= ' ' => {
+                    self.avanzar();
+                    texto.push(c);
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    /// Lee un escape \u ya sin la u, juntando los pares sustitutos
+    fn unicode(&mut self) -> Result<char, ErrorParseo> {
+        let primero = self.cuatro_hexadecimales()?;
+        let codigo = if (0xD800..0xDC00).contains(&primero) {
+            self.esperar('\\')?;
+            self.esperar('u')?;
+            let segundo = self.cuatro_hexadecimales()?;
+            if !(0xDC00..0xE000).contains(&segundo) {
+                return Err(self.error());
+            }
+            0x10000 + ((primero - 0xD800) << 10) + (segundo - 0xDC00)
+        } else {
+            primero
+        };
+        char::from_u32(codigo).ok_or_else(|| self.error())
+    }
+
+    fn cuatro_hexadecimales(&mut self) -> Result<u32, ErrorParseo> {
+        let mut codigo = 0;
+        for _ in 0..4 {
+            let digito = self.mirar().and_then(|c| c.to_digit(16));
+            let Some(digito) = digito else {
+                return Err(self.error());
+            };
+            self.avanzar();
+            codigo = codigo * 16 + digito;
+        }
+        Ok(codigo)
+    }
+
+    fn lista(&mut self, profundidad: usize) -> Result<Vec<Valor<'a>>, ErrorParseo> {
+        self.esperar('[')?;
+        let mut valores = Vec::new();
+        self.saltear_espacios();
+        if self.mirar() == Some(']') {
+            self.avanzar();
+            return Ok(valores);
+        }
+        loop {
+            valores.push(self.valor(profundidad + 1)?);
+            self.saltear_espacios();
+            match self.mirar() {
+                Some(',') => self.avanzar(),
+                Some(']') => {
+                    self.avanzar();
+                    return Ok(valores);
+                }
+                _ => return Err(self.error()),
+            };
+        }
+    }
+
+    fn objeto(&mut self, profundidad: usize) -> Result<Vec<(String, Valor<'a>)>, ErrorParseo> {
+        self.esperar('{')?;
+        let mut campos = Vec::new();
+        self.saltear_espacios();
+        if self.mirar() == Some('}') {
+            self.avanzar();
+            return Ok(campos);
+        }
+        loop {
+            self.saltear_espacios();
+            let clave = self.texto()?;
+            self.saltear_espacios();
+            self.esperar(':')?;
+            campos.push((clave, self.valor(profundidad + 1)?));
+            self.saltear_espacios();
+            match self.mirar() {
+                Some(',') => self.avanzar(),
+                Some('}') => {
+                    self.avanzar();
+                    return Ok(campos);
+                }
+                _ => return Err(self.error()),
+            };
+        }
+    }
+}
+
+/// Parsea una escena en JSON y devuelve el angulo, si venia, y los flatlanders
+///
+/// La entrada puede ser una lista de objetos con x y h, o un objeto con angulo y flatlanders
+/// donde flatlanders es esa lista. En cada objeto los campos nombre y grupo van a la identidad y
+/// los demas valores simples quedan como atributos
+///
+/// Los numeros se validan con los mismos limites que en los otros formatos
+pub fn parsear_json(
+    entrada: &str,
+    limites: Option<&Limites>,
+) -> Result<(Option<f64>, Vec<Flatlander>), ErrorParseo> {
+    let limites = limites.copied().unwrap_or_default();
+    let raiz = LectorJson::nuevo(entrada).documento()?;
+    let (angulo, lista) = match &raiz.tipo {
+        Tipo::Lista(_) => (None, &raiz),
+        Tipo::Objeto(_) => {
+            let angulo = match raiz.campo("angulo") {
+                Some(valor) => Some(parsear_angulo(
+                    &valor.token()?,
+                    &limites,
+                    &OpcionesDeParseo::default(),
+                )?),
+                None => None,
+            };
+            let lista = raiz.campo("flatlanders").ok_or_else(|| raiz.error())?;
+            (angulo, lista)
+        }
+        _ => return Err(raiz.error()),
+    };
+    let Tipo::Lista(valores) = &lista.tipo else {
+        return Err(lista.error());
+    };
+
+    let mut flatlanders = Vec::with_capacity(valores.len());
+    for valor in valores {
+        let Tipo::Objeto(campos) = &valor.tipo else {
+            return Err(valor.error());
+        };
+        let faltante = || ErrorParseo::ValorFaltante {
+            linea: valor.linea,
+            columna: valor.columna,
+        };
+        let x = valor.campo("x").ok_or_else(faltante)?;
+        let h = valor.campo("h").ok_or_else(faltante)?;
+        let flatlander = Flatlander::nuevo(
+            x.numero(limites.x_min, limites.x_max)?,
+            h.numero(limites.h_min, limites.h_max)?,
+        );
+
+        let mut identidad = Identidad::default();
+        for (clave, campo) in campos {
+            match clave.as_str() {
+                "x" | "h" => {}
+                "nombre" => identidad.nombre = Some(campo.como_texto()?),
+                "grupo" => identidad.grupo = Some(campo.como_texto()?),
+                _ => identidad
+                    .atributos
+                    .push((clave.clone(), campo.como_texto()?)),
+            }
+        }
+        flatlanders.push(if identidad == Identidad::default() {
+            flatlander
+        } else {
+            flatlander.con_identidad(identidad)
+        });
+    }
+    validar_cantidad(flatlanders.len(), &limites, lista.linea, lista.columna)?;
+    Ok((angulo, flatlanders))
+}
+
+/// Escapa el texto para ponerlo entre comillas en JSON
+pub(crate) fn escapar(texto: &str) -> String {
+    let mut escapado = String::with_capacity(texto.len() + 2);
+    escapado.push('"');
+    for c in texto.chars() {
+        match c {
+            '"' => escapado.push_str("\\\""),
+            '\\' => escapado.push_str("\\\\"),
+            '\n' => escapado.push_str("\\n"),
+            '\r' => escapado.push_str("\\r"),
+            '\t' => escapado.push_str("\\t"),
+            c if c < ' ' => {
+                let _ = write!(escapado, "\\u{:04x}", c as u32);
+            }
+            c => escapado.push(c),
+        }
+    }
+    escapado.push('"');
+    escapado
+}
+
+/// Escribe la escena en JSON, en el mismo formato que lee parsear_json
+///
+/// Los numeros se escriben con la representacion mas corta que vuelve al mismo f64
+pub fn escena_a_json(angulo: Option<f64>, flatlanders: &[Flatlander]) -> String {
+    let objetos: Vec<String> = flatlanders
+        .iter()
+        .map(|flatlander| {
+            let mut objeto = format!("{{\"x\":{},\"h\":{}", flatlander.x, flatlander.h);
+            if let Some(identidad) = flatlander.identidad.as_deref() {
+                if let Some(nombre) = &identidad.nombre {
+                    let _ = write!(objeto, ",\"nombre\":{}", escapar(nombre));
+                }
+                if let Some(grupo) = &identidad.grupo {
+                    let _ = write!(objeto, ",\"grupo\":{}", escapar(grupo));
+                }
+                for (clave, valor) in &identidad.atributos {
+                    let _ = write!(objeto, ",{}:{}", escapar(clave), escapar(valor));
+                }
+            }
+            objeto.push('}');
+            objeto
+        })
+        .collect();
+    let lista = format!("[{}]", objetos.join(","));
+    match angulo {
+        Some(angulo) => format!("{{\"angulo\":{angulo},\"flatlanders\":{lista}}}"),
+        None => lista,
+    }
+}
+
+/// Funcion interna que escribe un numero en JSON, que no tiene infinitos ni NaN, asi que van como null
+fn numero_json(valor: f64) -> String {
+    if valor.is_finite() {
+        valor.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Escribe el resumen en JSON con los parametros, el total y las regiones a la sombra
+///
+/// Los limites que no son finitos, como un x_max infinito, se escriben como null
+pub fn resumen_a_json(resumen: &ResumenDeEscena) -> String {
+    let limites = &resumen.limites;
+    let union: Vec<String> = resumen
+        .union
+        .iter()
+        .map(|i| format!("{{\"inicio\":{},\"fin\":{}}}", i.inicio, i.fin))
+        .collect();
+    format!(
+        "{{\"parametros\":{{\"angulo\":{},\"n\":{},\"limites\":{{\"n_min\":{},\"n_max\":{},\
+         \"x_min\":{},\"x_max\":{},\"h_min\":{},\"h_max\":{},\"angulo_min\":{},\"angulo_max\":{}}}}},\
+         \"total\":{},\"union\":[{}]}}",
+        resumen.angulo,
+        resumen.n,
+        limites.n_min,
+        limites.n_max,
+        numero_json(limites.x_min),
+        numero_json(limites.x_max),
+        numero_json(limites.h_min),
+        numero_json(limites.h_max),
+        numero_json(limites.angulo_min),
+        numero_json(limites.angulo_max),
+        resumen.total,
+        union.join(",")
+    )
+}
+
+#[test]
+fn parsea_lista_y_objeto() {
+    let lista =
+        "[{\"x\": 0, \"h\": 10}, {\"h\": 1e1, \"x\": 5, \"nombre\": \"b\\u00f3\", \"edad\": 3}]";
+    let (angulo, flatlanders) = parsear_json(lista, None).unwrap();
+    assert_eq!(angulo, None);
+    assert_eq!(flatlanders[0], Flatlander::nuevo(0.0, 10.0));
+    assert_eq!((flatlanders[1].x, flatlanders[1].h), (5.0, 10.0));
+    assert_eq!(flatlanders[1].nombre(), Some("bó"));
+    assert_eq!(
+        flatlanders[1].identidad.as_ref().unwrap().atributos,
+        vec![("edad".to_string(), "3".to_string())]
+    );
+
+    let objeto = "{\n  \"angulo\": 45,\n  \"flatlanders\": [{\"x\": 1, \"h\": 2}]\n}";
+    let (angulo, flatlanders) = parsear_json(objeto, None).unwrap();
+    assert_eq!(angulo, Some(45.0));
+    assert_eq!(flatlanders, vec![Flatlander::nuevo(1.0, 2.0)]);
+}
+
+#[test]
+fn errores_de_json() {
+    assert_eq!(
+        parsear_json("[{\"x\": 1,\n \"h\": 2,}]", None),
+        Err(ErrorParseo::FormatoInvalido {
+            linea: 2,
+            columna: 9,
+            token: "}".to_string(),
+        })
+    );
+    assert_eq!(
+        parsear_json("[{\"x\": 1}]", None),
+        Err(ErrorParseo::ValorFaltante {
+            linea: 1,
+            columna: 2,
+        })
+    );
+    assert!(matches!(
+        parsear_json("[{\"x\": 1, \"h\": -2}]", None),
+        Err(ErrorParseo::FueraDeRango { columna: 16, .. })
+    ));
+    assert!(matches!(
+        parsear_json(
+            "{\"angulo\": 85, \"flatlanders\": [{\"x\": 1, \"h\": 2}]}",
+            None
+        ),
+        Err(ErrorParseo::FueraDeRango { columna: 12, .. })
+    ));
+    assert!(matches!(
+        parsear_json("[]", None),
+        Err(ErrorParseo::FueraDeRango { .. })
+    ));
+    let profundo = "[".repeat(1000);
+    assert!(matches!(
+        parsear_json(&profundo, None),
+        Err(ErrorParseo::FormatoInvalido { .. })
+    ));
+}
+
+#[test]
+fn json_ida_y_vuelta() {
+    let flatlanders = vec![
+        Flatlander::nuevo(0.1, 10.0).con_identidad(Identidad {
+            nombre: Some("dice \"hola\"".to_string()),
+            grupo: Some("a".to_string()),
+            atributos: vec![("k".to_string(), "v".to_string())],
+        }),
+        Flatlander::nuevo(7.0, 2.25),
+    ];
+    let json = escena_a_json(Some(30.0), &flatlanders);
+    assert_eq!(parsear_json(&json, None), Ok((Some(30.0), flatlanders)));
+}
+
+#[test]
+fn resumen_en_json() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(5.0, 10.0)];
+    let resumen = ResumenDeEscena::calcular(45.0, &flatlanders, &Limites::default());
+    let json = resumen_a_json(&resumen);
+    assert!(json.starts_with("{\"parametros\":{\"angulo\":45,\"n\":2,\"limites\":{\"n_min\":1,"));
+    assert!(json.contains("\"union\":[{\"inicio\":0,\"fin\":"));
+    assert!(LectorJson::nuevo(&json).documento().is_ok());
+}
+
+#[test]
+fn resumen_con_limites_infinitos_sigue_siendo_json() {
+    let limites = Limites {
+        x_max: f64::INFINITY,
+        h_min: f64::NEG_INFINITY,
+        ..Limites::default()
+    };
+    let resumen = ResumenDeEscena::calcular(45.0, &[Flatlander::nuevo(0.0, 10.0)], &limites);
+    let json = resumen_a_json(&resumen);
+    assert!(json.contains("\"x_max\":null,\"h_min\":null,"));
+    assert!(LectorJson::nuevo(&json).documento().is_ok());
+}
@@ -0,0 +1,203 @@
+/// Archivo con los formatos de entrada y salida que entiende el binario
+use crate::escena_anotada::es_escena_anotada;
+use crate::flatlander::Flatlander;
+use crate::intervalo::Intervalo;
+use crate::longitud_union::{longitud_total, sombras_desde_flatlanders, union_de_intervalos};
+use crate::parseador::Limites;
+use std::str::FromStr;
+
+/// Formato en el que viene la escena
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatoDeEntrada {
+    /// El de la consigna: angulo y n en la primera linea y un flatlander por linea
+    Clasico,
+    /// El formato anotado que empieza con la marca #v2
+    Anotado,
+    /// Una fila de encabezado con las columnas x y h y un flatlander por fila
+    Csv,
+    /// Una lista de objetos con x y h, o un objeto con el angulo y esa lista en flatlanders
+    Json,
+}
+
+impl FormatoDeEntrada {
+    /// Adivina el formato mirando el primer byte que no sea un espacio
+    ///
+    /// Alcanza con el principio de la entrada, si no hay nada para mirar se asume el clasico. El
+    /// formato anotado solo se reconoce si la marca es lo primero de todo, como pide su parseador.
+    /// Para CSV la primera linea tiene que ser un encabezado con las columnas x y h, asi una
+    /// entrada clasica con un encabezado roto sigue dando el error del formato clasico
+    pub fn detectar(principio: &[u8]) -> Self {
+        if es_escena_anotada(principio) {
+            return FormatoDeEntrada::Anotado;
+        }
+        let principio = principio.strip_prefix(b"\xef\xbb\xbf").unwrap_or(principio);
+        let desde = principio
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(principio.len());
+        let primera_linea = principio[desde..]
+            .split(|b| *b == b'\n')
+            .next()
+            .unwrap_or_default();
+        match primera_linea.first() {
+            Some(b'[' | b'{') => FormatoDeEntrada::Json,
+            _ if es_encabezado_csv(primera_linea) => FormatoDeEntrada::Csv,
+            _ => FormatoDeEntrada::Clasico,
+        }
+    }
+}
+
+/// Funcion interna que dice si la linea es un encabezado CSV con las columnas x y h
+///
+/// Acepta los dos separadores de campos, la coma y el punto y coma de la coma decimal
+fn es_encabezado_csv(linea: &[u8]) -> bool {
+    [b',', b';'].iter().any(|separador| {
+        let columnas: Vec<Vec<u8>> = linea
+            .split(|b| b == separador)
+            .map(|campo| {
+                let campo = campo.trim_ascii();
+                let campo = campo
+                    .strip_prefix(b"\"")
+                    .and_then(|c| c.strip_suffix(b"\""))
+                    .unwrap_or(campo);
+                campo.trim_ascii().to_ascii_lowercase()
+            })
+            .collect();
+        columnas.len() > 1
+            && columnas.iter().any(|c| c == b"x")
+            && columnas.iter().any(|c| c == b"h")
+    })
+}
+
+impl FromStr for FormatoDeEntrada {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clasico" => Ok(FormatoDeEntrada::Clasico),
+            "anotado" => Ok(FormatoDeEntrada::Anotado),
+            "csv" => Ok(FormatoDeEntrada::Csv),
+            "json" => Ok(FormatoDeEntrada::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Formato en el que se imprime el resultado
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormatoDeSalida {
+    /// El total con 13 decimales, como siempre
+    #[default]
+    Texto,
+    /// Una fila de encabezado y una fila con el angulo, n y el total
+    Csv,
+    /// Un objeto con los parametros, el total y las regiones a la sombra
+    Json,
+}
+
+impl FromStr for FormatoDeSalida {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "texto" => Ok(FormatoDeSalida::Texto),
+            "csv" => Ok(FormatoDeSalida::Csv),
+            "json" => Ok(FormatoDeSalida::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Resultado de una escena junto con los parametros con los que se calculo
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResumenDeEscena {
+    /// Angulo del sol
+    pub angulo: f64,
+    /// Cantidad de flatlanders
+    pub n: usize,
+    /// Limites con los que se valido la entrada
+    pub limites: Limites,
+    /// Longitud total a la sombra
+    pub total: f64,
+    /// Regiones a la sombra ya unidas, ordenadas de izquierda a derecha
+    pub union: Vec<Intervalo>,
+}
+
+impl ResumenDeEscena {
+    /// Calcula la sombra de la escena y arma el resumen
+    pub fn calcular(angulo: f64, flatlanders: &[Flatlander], limites: &Limites) -> Self {
+        let union = union_de_intervalos(sombras_desde_flatlanders(flatlanders, angulo));
+        let total = longitud_total(&union);
+        Self {
+            angulo,
+            n: flatlanders.len(),
+            limites: *limites,
+            total,
+            union,
+        }
+    }
+}
+
+#[test]
+fn detecta_formatos() {
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"45 3\n"),
+        FormatoDeEntrada::Clasico
+    );
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"  \n[{\"x\":1}]"),
+        FormatoDeEntrada::Json
+    );
+    assert_eq!(FormatoDeEntrada::detectar(b"{}"), FormatoDeEntrada::Json);
+    assert_eq!(FormatoDeEntrada::detectar(b"x,h\n"), FormatoDeEntrada::Csv);
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"\xef\xbb\xbfx,h\n"),
+        FormatoDeEntrada::Csv
+    );
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"#v2\n"),
+        FormatoDeEntrada::Anotado
+    );
+    assert_eq!(FormatoDeEntrada::detectar(b""), FormatoDeEntrada::Clasico);
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"\"H\",\"X\"\r\n"),
+        FormatoDeEntrada::Csv
+    );
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"\"H\";\"X\"\r\n"),
+        FormatoDeEntrada::Csv
+    );
+}
+
+#[test]
+fn encabezado_clasico_roto_no_se_toma_como_csv() {
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"xx 2\n0 10\n"),
+        FormatoDeEntrada::Clasico
+    );
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"x h\n0 10\n"),
+        FormatoDeEntrada::Clasico
+    );
+    assert_eq!(
+        FormatoDeEntrada::detectar(b"a,b\n"),
+        FormatoDeEntrada::Clasico
+    );
+    assert!(matches!(
+        crate::parseador::parsear_encabezado(Some("xx 2"), None),
+        Err(crate::parseador::ErrorParseo::NumeroInvalido {
+            linea: 1,
+            columna: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn resumen_de_dos_flatlanders() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(5.0, 10.0)];
+    let resumen = ResumenDeEscena::calcular(45.0, &flatlanders, &Limites::default());
+    assert_eq!(resumen.n, 2);
+    assert!((resumen.total - 15.0).abs() < 1e-9);
+    assert_eq!(resumen.union.len(), 1);
+}
@@ -17,6 +17,60 @@ pub fn longitud_de_sombra(h: f64, angulo: f64) -> f64 {
     h / tangente(angulo)
 }
 
+/// Hacia donde cae la sombra
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direccion {
+    /// El sol esta a la izquierda, la sombra va de x hacia la derecha
+    Derecha,
+    /// El sol esta a la derecha, la sombra va de x hacia la izquierda
+    Izquierda,
+}
+
+impl Direccion {
+    /// Devuelve la direccion que corresponde a un angulo entre 0 y 180
+    ///
+    /// Hasta 90 el sol esta a la izquierda, pasando 90 esta a la derecha
+    pub fn desde_angulo(angulo: f64) -> Self {
+        if angulo > 90.0 {
+            Direccion::Izquierda
+        } else {
+            Direccion::Derecha
+        }
+    }
+}
+
+/// Pasa una elevacion sobre el horizonte (0 a 90) y una direccion al angulo entre 0 y 180
+pub fn angulo_desde_elevacion(elevacion: f64, direccion: Direccion) -> f64 {
+    match direccion {
+        Direccion::Derecha => elevacion,
+        Direccion::Izquierda => 180.0 - elevacion,
+    }
+}
+
+/// Devuelve true si el angulo da una sombra finita
+///
+/// Con 0 o 180 grados la tangente es 0 y la sombra seria infinita, por eso quedan afuera
+pub fn angulo_de_sol_valido(angulo: f64) -> bool {
+    angulo > 0.0 && angulo < 180.0
+}
+
+/// Calcula cuanto se corre la punta de la sombra respecto de x, positivo a la derecha y negativo a la izquierda
+///
+/// En 90 grados la tangente da casi infinito, asi que ahi se fuerza la sombra a cero
+pub fn desplazamiento_de_sombra(h: f64, angulo: f64) -> f64 {
+    desplazamiento_con_tangente(h, angulo, tangente(angulo))
+}
+
+/// Igual que desplazamiento_de_sombra pero con la tangente del angulo ya calculada
+///
+/// Sirve para calcularla una sola vez cuando se usa el mismo angulo para muchos flatlanders
+pub fn desplazamiento_con_tangente(h: f64, angulo: f64, tangente: f64) -> f64 {
+    if angulo == 90.0 {
+        return 0.0;
+    }
+    h / tangente
+}
+
 #[test]
 fn tangente_45_grados_es_uno() {
     let tangente = tangente(45.0);
@@ -54,3 +108,39 @@ fn comparacion_con_formula_directa_en_radianes() {
     let tangente_funcion = tangente(angulo);
     assert!((tangente_directa - tangente_funcion).abs() < 1e-12);
 }
+
+#[test]
+fn desplazamiento_a_la_izquierda_pasando_90() {
+    let desplazamiento = desplazamiento_de_sombra(10.0, 135.0);
+    assert!((desplazamiento + 10.0).abs() < 1e-12);
+    assert_eq!(Direccion::desde_angulo(135.0), Direccion::Izquierda);
+}
+
+#[test]
+fn desplazamiento_con_tangente_da_los_mismos_bits() {
+    for angulo in [10.0, 33.7, 45.0, 90.0, 135.0, 179.9] {
+        let esperado = desplazamiento_de_sombra(12.3, angulo);
+        let obtenido = desplazamiento_con_tangente(12.3, angulo, tangente(angulo));
+        assert_eq!(obtenido.to_bits(), esperado.to_bits());
+    }
+}
+
+#[test]
+fn desplazamiento_en_90_es_cero() {
+    assert_eq!(desplazamiento_de_sombra(10.0, 90.0), 0.0);
+}
+
+#[test]
+fn angulos_degenerados_no_son_validos() {
+    assert!(!angulo_de_sol_valido(0.0));
+    assert!(!angulo_de_sol_valido(180.0));
+    assert!(!angulo_de_sol_valido(f64::NAN));
+    assert!(angulo_de_sol_valido(90.0));
+    assert!(angulo_de_sol_valido(179.9));
+}
+
+#[test]
+fn elevacion_y_direccion_dan_el_angulo() {
+    assert_eq!(angulo_desde_elevacion(30.0, Direccion::Derecha), 30.0);
+    assert_eq!(angulo_desde_elevacion(30.0, Direccion::Izquierda), 150.0);
+}
@@ -1,7 +1,7 @@
 /// Representa un intervalo en la recta
 ///
 /// Cada intervalo tiene su extremo inicio (izquierdo) y fin (derecho)
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Intervalo {
     /// Extremo izquierdo
     pub inicio: f64,
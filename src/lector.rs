@@ -0,0 +1,663 @@
+/// Archivo para parsear la entrada de a una linea, sin cargarla entera en memoria
+use crate::flatlander::Flatlander;
+use crate::parseador::{
+    Advertencia, ErrorParseo, Limites, ModoDeParseo, OpcionesDeParseo, parsear_linea_de_encabezado,
+    parsear_linea_de_flatlander, verificar_linea_sobrante,
+};
+use std::io::BufRead;
+
+/// Algo de donde se pueden sacar lineas de texto de a una
+///
+/// Lo implementan los str::Lines y LineasDeBufRead, asi el iterador de flatlanders sirve para los dos
+pub trait FuenteDeLineas {
+    /// Devuelve la siguiente linea sin el fin de linea, o None si se termino
+    fn siguiente_linea(&mut self) -> Option<Result<&str, ErrorParseo>>;
+}
+
+impl FuenteDeLineas for std::str::Lines<'_> {
+    fn siguiente_linea(&mut self) -> Option<Result<&str, ErrorParseo>> {
+        self.next().map(Ok)
+    }
+}
+
+impl<F: FuenteDeLineas + ?Sized> FuenteDeLineas for &mut F {
+    fn siguiente_linea(&mut self) -> Option<Result<&str, ErrorParseo>> {
+        (**self).siguiente_linea()
+    }
+}
+
+/// BOM de UTF-8 que ponen algunos editores de Windows al principio del archivo
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Lineas de un BufRead (un archivo, el stdin o un &[u8]) leidas siempre en el mismo buffer
+#[derive(Debug)]
+pub struct LineasDeBufRead<R> {
+    lector: R,
+    buffer: String,
+    bytes: Vec<u8>,
+    permisiva: bool,
+    primera: bool,
+}
+
+impl<R: BufRead> LineasDeBufRead<R> {
+    /// Envuelve el lector, el UTF-8 invalido es un error de IO
+    pub fn nuevo(lector: R) -> Self {
+        Self {
+            lector,
+            buffer: String::new(),
+            bytes: Vec::new(),
+            permisiva: false,
+            primera: true,
+        }
+    }
+
+    /// Envuelve el lector sacando el BOM del principio y reemplazando los bytes que no son UTF-8
+    pub fn permisiva(lector: R) -> Self {
+        Self {
+            permisiva: true,
+            ..Self::nuevo(lector)
+        }
+    }
+
+    /// Funcion interna que lee la linea como bytes y la pasa al buffer sin perder nada
+    fn leer_permisiva(&mut self) -> std::io::Result<usize> {
+        self.bytes.clear();
+        let leidos = self.lector.read_until(b'\n', &mut self.bytes)?;
+        let mut bytes = self.bytes.as_slice();
+        if self.primera {
+            bytes = bytes.strip_prefix(BOM).unwrap_or(bytes);
+        }
+        self.buffer.push_str(&String::from_utf8_lossy(bytes));
+        Ok(leidos)
+    }
+}
+
+impl<R: BufRead> FuenteDeLineas for LineasDeBufRead<R> {
+    fn siguiente_linea(&mut self) -> Option<Result<&str, ErrorParseo>> {
+        self.buffer.clear();
+        let leido = if self.permisiva {
+            self.leer_permisiva()
+        } else {
+            self.lector.read_line(&mut self.buffer)
+        };
+        self.primera = false;
+        match leido {
+            Ok(0) => None,
+            Ok(_) => {
+                // igual que str::lines, se saca el \n y el \r de antes si lo hay
+                if self.buffer.ends_with('\n') {
+                    self.buffer.pop();
+                    if self.buffer.ends_with('\r') {
+                        self.buffer.pop();
+                    }
+                }
+                Some(Ok(&self.buffer))
+            }
+            Err(e) => Some(Err(ErrorParseo::Io {
+                mensaje: e.to_string(),
+            })),
+        }
+    }
+}
+
+/// Iterador perezoso que parsea un flatlander por linea
+///
+/// Se acuerda de la linea en la que va y de cuantos de los n flatlanders ya devolvio,
+/// despues de los n o de un error devuelve None
+///
+/// En modo estricto, despues de los n se fija que no haya mas datos, y en modo permisivo
+/// sigue hasta el final de la entrada sin importar n
+#[derive(Debug)]
+pub struct IteradorDeFlatlanders<F> {
+    fuente: F,
+    limites: Limites,
+    opciones: OpcionesDeParseo,
+    numero_linea: usize,
+    n: usize,
+    leidos: usize,
+    fallidos: usize,
+    seguir: bool,
+    terminado: bool,
+    advertencias: Vec<Advertencia>,
+}
+
+impl<F: FuenteDeLineas> IteradorDeFlatlanders<F> {
+    /// Crea el iterador para n flatlanders
+    ///
+    /// Igual que parsear_flatlanders se asume que el encabezado ya se leyo, asi que el primero esta en la linea 2
+    ///
+    /// Si no se pasan limites se usan los de la consigna
+    pub fn nuevo(fuente: F, n: usize, limites: Option<&Limites>) -> Self {
+        Self {
+            fuente,
+            limites: limites.copied().unwrap_or_default(),
+            opciones: OpcionesDeParseo::default(),
+            numero_linea: 1,
+            n,
+            leidos: 0,
+            fallidos: 0,
+            seguir: false,
+            terminado: false,
+            advertencias: Vec::new(),
+        }
+    }
+
+    /// Cambia la cantidad de lineas ya leidas antes del primer flatlander, para que los errores tengan la linea bien
+    pub fn despues_de_linea(mut self, numero_linea: usize) -> Self {
+        self.numero_linea = numero_linea;
+        self
+    }
+
+    /// Cambia las opciones de parseo
+    pub fn con_opciones(mut self, opciones: OpcionesDeParseo) -> Self {
+        self.opciones = opciones;
+        self
+    }
+
+    /// Sigue despues de una linea con error en vez de cortar ahi, para juntar todos los errores
+    ///
+    /// La linea con error ocupa el lugar de un flatlander. Los errores de IO y las lineas que faltan
+    /// siguen cortando porque despues de eso no hay nada mas para leer
+    pub fn seguir_despues_de_errores(mut self) -> Self {
+        self.seguir = true;
+        self
+    }
+
+    /// Ultima linea leida
+    pub fn numero_linea(&self) -> usize {
+        self.numero_linea
+    }
+
+    /// Cantidad de flatlanders del encabezado
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Cantidad de flatlanders ya devueltos
+    pub fn leidos(&self) -> usize {
+        self.leidos
+    }
+
+    /// Cantidad de lineas con error que ya se devolvieron, solo crece si se sigue despues de los errores
+    pub fn fallidos(&self) -> usize {
+        self.fallidos
+    }
+
+    /// Cantidad de flatlanders que faltan segun el encabezado
+    pub fn restantes(&self) -> usize {
+        self.n.saturating_sub(self.leidos + self.fallidos)
+    }
+
+    /// Funcion interna que anota el resultado de una linea y decide si hay que cortar
+    fn anotar(&mut self, resultado: &Result<Flatlander, ErrorParseo>) {
+        match resultado {
+            Ok(_) => self.leidos += 1,
+            Err(ErrorParseo::Io { .. } | ErrorParseo::LineaFaltante { .. }) => {
+                self.terminado = true
+            }
+            Err(_) if self.seguir => self.fallidos += 1,
+            Err(_) => self.terminado = true,
+        }
+    }
+
+    /// Avisos que se juntaron hasta ahora, solo en modo permisivo
+    pub fn advertencias(&self) -> &[Advertencia] {
+        &self.advertencias
+    }
+
+    /// Funcion interna para el modo normal y el estricto: la siguiente linea tiene que ser un flatlander
+    fn siguiente_normal(&mut self) -> Option<Result<Flatlander, ErrorParseo>> {
+        let numero_linea = self.numero_linea + 1;
+        let resultado = match self.fuente.siguiente_linea() {
+            Some(Ok(linea)) => {
+                self.numero_linea = numero_linea;
+                parsear_linea_de_flatlander(linea, numero_linea, &self.limites, &self.opciones)
+            }
+            Some(Err(error)) => Err(error),
+            None => Err(ErrorParseo::LineaFaltante {
+                linea: numero_linea,
+            }),
+        };
+        self.anotar(&resultado);
+        Some(resultado)
+    }
+
+    /// Funcion interna para el modo permisivo: saltea lineas en blanco y sigue hasta el final
+    fn siguiente_permisivo(&mut self) -> Option<Result<Flatlander, ErrorParseo>> {
+        loop {
+            let numero_linea = self.numero_linea + 1;
+            let resultado = match self.fuente.siguiente_linea() {
+                Some(Ok(linea)) if linea.trim().is_empty() => None,
+                Some(Ok(linea)) => Some(parsear_linea_de_flatlander(
+                    linea,
+                    numero_linea,
+                    &self.limites,
+                    &self.opciones,
+                )),
+                Some(Err(error)) => Some(Err(error)),
+                None => {
+                    self.terminado = true;
+                    if self.leidos + self.fallidos != self.n {
+                        self.advertencias.push(Advertencia::CantidadDistinta {
+                            declarada: self.n,
+                            encontrada: self.leidos + self.fallidos,
+                        });
+                    }
+                    return None;
+                }
+            };
+            self.numero_linea = numero_linea;
+            if let Some(resultado) = resultado {
+                self.anotar(&resultado);
+                return Some(resultado);
+            }
+        }
+    }
+
+    /// Funcion interna que lee lo que queda de la fuente sin parsearlo, solo para que un error de IO
+    /// en el resto no pase de largo
+    fn leer_el_resto(&mut self) -> Option<Result<Flatlander, ErrorParseo>> {
+        self.terminado = true;
+        while let Some(linea) = self.fuente.siguiente_linea() {
+            if let Err(error) = linea {
+                return Some(Err(error));
+            }
+        }
+        None
+    }
+
+    /// Funcion interna para el modo estricto: despues de los n solo puede haber lineas en blanco
+    fn verificar_el_final(&mut self) -> Option<Result<Flatlander, ErrorParseo>> {
+        loop {
+            let numero_linea = self.numero_linea + 1;
+            let sobrante = match self.fuente.siguiente_linea() {
+                Some(Ok(linea)) => verificar_linea_sobrante(linea, numero_linea),
+                Some(Err(error)) => Err(error),
+                None => {
+                    self.terminado = true;
+                    return None;
+                }
+            };
+            self.numero_linea = numero_linea;
+            if let Err(error) = sobrante {
+                self.terminado = !self.seguir || matches!(error, ErrorParseo::Io { .. });
+                return Some(Err(error));
+            }
+        }
+    }
+}
+
+impl<F: FuenteDeLineas> Iterator for IteradorDeFlatlanders<F> {
+    type Item = Result<Flatlander, ErrorParseo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.terminado {
+            return None;
+        }
+        match self.opciones.modo {
+            ModoDeParseo::Permisivo => self.siguiente_permisivo(),
+            ModoDeParseo::Estricto if self.restantes() == 0 => self.verificar_el_final(),
+            _ if self.restantes() == 0 => None,
+            _ => self.siguiente_normal(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.terminado {
+            (0, Some(0))
+        } else if self.opciones.modo == ModoDeParseo::Permisivo {
+            (0, None)
+        } else {
+            (0, Some(self.restantes()))
+        }
+    }
+}
+
+/// Lee el encabezado de un BufRead y despues devuelve los flatlanders de a uno, reusando siempre el mismo buffer
+///
+/// Los errores salen apenas llega la linea que los tiene, sin esperar al resto de la entrada
+///
+/// Despues del flatlander n se lee el resto de a una linea sin guardarlo, asi el UTF-8 invalido
+/// que venga despues es un error de IO igual que en leer_entrada_desde
+#[derive(Debug)]
+pub struct LectorDeEscena<R> {
+    angulo: f64,
+    flatlanders: IteradorDeFlatlanders<LineasDeBufRead<R>>,
+}
+
+impl<R: BufRead> LectorDeEscena<R> {
+    /// Crea el lector y parsea el encabezado
+    ///
+    /// Si no se pasan limites se usan los de la consigna
+    pub fn nuevo(lector: R, limites: Option<&Limites>) -> Result<Self, ErrorParseo> {
+        Self::con_opciones(lector, limites, OpcionesDeParseo::default())
+    }
+
+    /// Igual que nuevo pero con opciones de parseo
+    pub fn con_opciones(
+        lector: R,
+        limites: Option<&Limites>,
+        opciones: OpcionesDeParseo,
+    ) -> Result<Self, ErrorParseo> {
+        let limites = limites.copied().unwrap_or_default();
+        let permisivo = opciones.modo == ModoDeParseo::Permisivo;
+        let mut lineas = if permisivo {
+            LineasDeBufRead::permisiva(lector)
+        } else {
+            LineasDeBufRead::nuevo(lector)
+        };
+        let mut numero_linea = 0;
+        let (angulo, n) = loop {
+            numero_linea += 1;
+            let linea = lineas
+                .siguiente_linea()
+                .ok_or(ErrorParseo::LineaFaltante {
+                    linea: numero_linea,
+                })??;
+            if permisivo && linea.trim().is_empty() {
+                continue;
+            }
+            break parsear_linea_de_encabezado(linea, numero_linea, &limites, &opciones)?;
+        };
+        Ok(Self {
+            angulo,
+            flatlanders: IteradorDeFlatlanders::nuevo(lineas, n, Some(&limites))
+                .con_opciones(opciones)
+                .despues_de_linea(numero_linea),
+        })
+    }
+
+    /// Angulo del encabezado
+    pub fn angulo(&self) -> f64 {
+        self.angulo
+    }
+
+    /// Cantidad de flatlanders del encabezado
+    pub fn n(&self) -> usize {
+        self.flatlanders.n()
+    }
+
+    /// Ultima linea leida
+    pub fn numero_linea(&self) -> usize {
+        self.flatlanders.numero_linea()
+    }
+
+    /// Cantidad de flatlanders ya devueltos
+    pub fn leidos(&self) -> usize {
+        self.flatlanders.leidos()
+    }
+
+    /// Avisos que se juntaron hasta ahora, solo en modo permisivo
+    pub fn advertencias(&self) -> &[Advertencia] {
+        self.flatlanders.advertencias()
+    }
+}
+
+impl<R: BufRead> Iterator for LectorDeEscena<R> {
+    type Item = Result<Flatlander, ErrorParseo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let siguiente = self.flatlanders.next();
+        // el iterador para en el flatlander n sin haber terminado la entrada
+        if siguiente.is_none() && !self.flatlanders.terminado {
+            return self.flatlanders.leer_el_resto();
+        }
+        siguiente
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (minimo, maximo) = self.flatlanders.size_hint();
+        if self.flatlanders.terminado {
+            return (minimo, maximo);
+        }
+        // despues de los n todavia puede salir el error de IO del resto de la entrada
+        (minimo, maximo.and_then(|maximo| maximo.checked_add(1)))
+    }
+}
+
+/// Parsea toda la escena leyendo de a una linea
+///
+/// Devuelve lo mismo que leer_entrada_desde seguido de parsear_encabezado y parsear_flatlanders.
+/// Si la entrada tiene una linea con error y tambien UTF-8 invalido mas adelante, aca sale el error
+/// de la linea, que es el que se encuentra primero
+pub fn parsear_escena_desde(
+    lector: impl BufRead,
+    limites: Option<&Limites>,
+) -> Result<(f64, Vec<Flatlander>), ErrorParseo> {
+    parsear_escena_con_opciones(lector, limites, OpcionesDeParseo::default())
+}
+
+/// Igual que parsear_escena_desde pero con opciones de parseo
+pub fn parsear_escena_con_opciones(
+    lector: impl BufRead,
+    limites: Option<&Limites>,
+    opciones: OpcionesDeParseo,
+) -> Result<(f64, Vec<Flatlander>), ErrorParseo> {
+    let lector_de_escena = LectorDeEscena::con_opciones(lector, limites, opciones)?;
+    let angulo = lector_de_escena.angulo();
+    let flatlanders = lector_de_escena.collect::<Result<Vec<_>, _>>()?;
+    Ok((angulo, flatlanders))
+}
+
+#[test]
+fn lee_la_escena_de_a_una_linea() {
+    let entrada = "30 3\n50 150\n0 100\n100 200\nbasura que no se parsea\n";
+    let (angulo, flatlanders) = parsear_escena_desde(entrada.as_bytes(), None).unwrap();
+    assert_eq!(angulo, 30.0);
+    assert_eq!(flatlanders.len(), 3);
+    assert_eq!(flatlanders[2], Flatlander::nuevo(100.0, 200.0));
+}
+
+#[test]
+fn acepta_fin_de_linea_de_windows() {
+    let entrada = "45 2\r\n0 10\r\n5 10";
+    let (_, flatlanders) = parsear_escena_desde(entrada.as_bytes(), None).unwrap();
+    assert_eq!(flatlanders[1], Flatlander::nuevo(5.0, 10.0));
+}
+
+#[test]
+fn error_sale_en_la_linea_que_lo_tiene() {
+    let entrada = "45 3\n0 10\n5 x\n";
+    let mut lector = LectorDeEscena::nuevo(entrada.as_bytes(), None).unwrap();
+    assert!(lector.next().unwrap().is_ok());
+    assert_eq!(
+        lector.next(),
+        Some(Err(ErrorParseo::NumeroInvalido {
+            linea: 3,
+            columna: 3,
+            token: "x".to_string(),
+        }))
+    );
+    assert!(lector.next().is_none());
+}
+
+#[test]
+fn linea_faltante_al_final() {
+    let entrada = "45 3\n0 10\n";
+    let resultado = parsear_escena_desde(entrada.as_bytes(), None);
+    assert_eq!(resultado, Err(ErrorParseo::LineaFaltante { linea: 3 }));
+}
+
+#[test]
+fn entrada_vacia_no_tiene_encabezado() {
+    let resultado = parsear_escena_desde("".as_bytes(), None);
+    assert_eq!(resultado, Err(ErrorParseo::LineaFaltante { linea: 1 }));
+}
+
+#[test]
+fn utf8_invalido_es_error_de_io() {
+    let entrada: &[u8] = b"45 1\n\xff 10\n";
+    let resultado = parsear_escena_desde(entrada, None);
+    assert!(matches!(resultado, Err(ErrorParseo::Io { .. })));
+}
+
+#[test]
+fn iterador_desde_lineas_lleva_la_cuenta() {
+    let entrada = "0 10\n5 20\n7 30\n";
+    let mut iterador = IteradorDeFlatlanders::nuevo(entrada.lines(), 3, None);
+    assert_eq!(iterador.next(), Some(Ok(Flatlander::nuevo(0.0, 10.0))));
+    assert_eq!(iterador.leidos(), 1);
+    assert_eq!(iterador.numero_linea(), 2);
+    assert_eq!(iterador.restantes(), 2);
+    assert_eq!(iterador.count(), 2);
+}
+
+#[test]
+fn iterador_desde_bytes() {
+    let entrada: &[u8] = b"0 10\n5 20\n";
+    let iterador = IteradorDeFlatlanders::nuevo(LineasDeBufRead::nuevo(entrada), 2, None);
+    let flatlanders: Vec<_> = iterador.collect::<Result<_, _>>().unwrap();
+    assert_eq!(flatlanders[1], Flatlander::nuevo(5.0, 20.0));
+}
+
+#[test]
+fn iterador_comparte_las_lineas_con_el_encabezado() {
+    use crate::parseador::parsear_encabezado;
+    let entrada = "45 1\n0 10\n45 1\n3 x\n";
+    let mut lineas = entrada.lines();
+    let (_, n) = parsear_encabezado(lineas.next(), None).unwrap();
+    let primero: Vec<_> = IteradorDeFlatlanders::nuevo(&mut lineas, n, None).collect();
+    assert_eq!(primero, vec![Ok(Flatlander::nuevo(0.0, 10.0))]);
+    let (_, n) = parsear_encabezado(lineas.next(), None).unwrap();
+    let mut segundo = IteradorDeFlatlanders::nuevo(&mut lineas, n, None).despues_de_linea(3);
+    assert_eq!(
+        segundo.next(),
+        Some(Err(ErrorParseo::NumeroInvalido {
+            linea: 4,
+            columna: 3,
+            token: "x".to_string(),
+        }))
+    );
+}
+
+#[test]
+fn iterador_se_compone_con_las_sombras_sin_vectores_intermedios() {
+    use crate::longitud_union::{longitud_union, sombras_desde_iterador};
+    let entrada = "0 10\n5 3\n20 10\n";
+    let altos = IteradorDeFlatlanders::nuevo(entrada.lines(), 3, None)
+        .filter_map(Result::ok)
+        .filter(|flatlander| flatlander.h >= 5.0);
+    let total = longitud_union(sombras_desde_iterador(altos, 45.0).collect());
+    assert!((total - 20.0).abs() < 1e-12);
+}
+
+#[cfg(test)]
+fn con_modo(modo: ModoDeParseo) -> OpcionesDeParseo {
+    OpcionesDeParseo {
+        modo,
+        ..OpcionesDeParseo::default()
+    }
+}
+
+#[test]
+fn estricto_rechaza_lineas_de_mas() {
+    let entrada = "45 1\n0 10\n\n5 10\n";
+    let opciones = con_modo(ModoDeParseo::Estricto);
+    let resultado = parsear_escena_con_opciones(entrada.as_bytes(), None, opciones);
+    assert_eq!(
+        resultado,
+        Err(ErrorParseo::DatosSobrantes {
+            linea: 4,
+            columna: 1,
+            token: "5".to_string(),
+        })
+    );
+}
+
+#[test]
+fn estricto_rechaza_tokens_de_mas() {
+    let entrada = "45 1\n0 10 7\n";
+    let opciones = con_modo(ModoDeParseo::Estricto);
+    let resultado = parsear_escena_con_opciones(entrada.as_bytes(), None, opciones);
+    assert_eq!(
+        resultado,
+        Err(ErrorParseo::DatosSobrantes {
+            linea: 2,
+            columna: 6,
+            token: "7".to_string(),
+        })
+    );
+    let encabezado = parsear_escena_con_opciones("45 1 x\n0 10\n".as_bytes(), None, opciones);
+    assert!(matches!(
+        encabezado,
+        Err(ErrorParseo::DatosSobrantes { linea: 1, .. })
+    ));
+}
+
+#[test]
+fn estricto_acepta_lineas_en_blanco_al_final() {
+    let entrada = "45 1\n0 10\n\n  \n";
+    let opciones = con_modo(ModoDeParseo::Estricto);
+    assert!(parsear_escena_con_opciones(entrada.as_bytes(), None, opciones).is_ok());
+}
+
+#[test]
+fn permisivo_saca_bom_saltea_blancos_y_deduce_n() {
+    let entrada = "\u{feff}\r\n45 2\r\n0 10\r\n\r\n5 10\r\n7 10\r\n";
+    let mut lector =
+        LectorDeEscena::con_opciones(entrada.as_bytes(), None, con_modo(ModoDeParseo::Permisivo))
+            .unwrap();
+    let flatlanders: Vec<_> = lector.by_ref().collect::<Result<_, _>>().unwrap();
+    assert_eq!(flatlanders.len(), 3);
+    assert_eq!(
+        lector.advertencias(),
+        &[Advertencia::CantidadDistinta {
+            declarada: 2,
+            encontrada: 3,
+        }]
+    );
+}
+
+#[test]
+fn permisivo_no_corta_con_utf8_invalido() {
+    let entrada: &[u8] = b"45 1\n\xff 10\n";
+    let resultado = parsear_escena_con_opciones(entrada, None, con_modo(ModoDeParseo::Permisivo));
+    assert!(matches!(
+        resultado,
+        Err(ErrorParseo::NumeroInvalido { linea: 2, .. })
+    ));
+}
+
+#[test]
+fn normal_sigue_ignorando_lo_que_sobra() {
+    let entrada = "45 1\n0 10 7\nbasura\n";
+    assert!(parsear_escena_desde(entrada.as_bytes(), None).is_ok());
+}
+
+#[test]
+fn coma_decimal_en_el_formato_clasico() {
+    let opciones = OpcionesDeParseo {
+        decimal: crate::numeros::SeparadorDecimal::Coma,
+        ..OpcionesDeParseo::default()
+    };
+    let entrada = "33,7 2\n1.500 12,5\n0,5 3\n";
+    let escena = parsear_escena_con_opciones(entrada.as_bytes(), None, opciones);
+    assert_eq!(
+        escena,
+        Ok((
+            33.7,
+            vec![Flatlander::nuevo(1500.0, 12.5), Flatlander::nuevo(0.5, 3.0)]
+        ))
+    );
+    let ambiguo = parsear_escena_con_opciones("45 1\n12.5 3\n".as_bytes(), None, opciones);
+    assert!(matches!(
+        ambiguo,
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 2,
+            columna: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn iterador_sigue_despues_de_los_errores() {
+    let entrada = "0 10\nx 20\n7 30\n";
+    let mut iterador =
+        IteradorDeFlatlanders::nuevo(entrada.lines(), 4, None).seguir_despues_de_errores();
+    let resultados: Vec<_> = iterador.by_ref().map(|r| r.is_ok()).collect();
+    assert_eq!(resultados, vec![true, false, true, false]);
+    assert_eq!(iterador.leidos(), 2);
+    assert_eq!(iterador.fallidos(), 1);
+    assert_eq!(iterador.next(), None);
+}
@@ -1,5 +1,26 @@
+pub mod angulo_inverso;
+pub mod animacion;
+pub mod argumentos;
+pub mod atribucion;
+pub mod barrido;
+pub mod cobertura;
+pub mod cobertura_por_angulo;
+pub mod diagnostico;
+pub mod dibujo_svg;
+pub mod dibujo_terminal;
+pub mod escena_anotada;
 pub mod flatlander;
+pub mod formato_csv;
+pub mod formato_json;
+pub mod formatos;
 pub mod geometria;
 pub mod intervalo;
+pub mod lector;
 pub mod longitud_union;
+pub mod lote;
+pub mod mapa_de_calor;
+pub mod mensajes;
+pub mod numeros;
 pub mod parseador;
+#[cfg(test)]
+mod pruebas;
@@ -1,67 +1,256 @@
 /// Archivo donde uno los tres mini crates 'teoricos'
 use crate::flatlander::Flatlander;
-use crate::geometria::longitud_de_sombra;
+use crate::geometria::{
+    Direccion, angulo_de_sol_valido, angulo_desde_elevacion, desplazamiento_de_sombra,
+};
 use crate::intervalo::Intervalo;
 
-/// Funcion interna para ordenar los intervalos con el algoritmo de seleccion
+/// Cantidad de intervalos a partir de la cual conviene el radix sort en vez de la comparacion
+const UMBRAL_RADIX: usize = 4096;
+
+/// Funcion interna para ordenar los intervalos por su inicio
 ///
-/// No estaba seguro si se podia usar sortBy()
+/// Si ya vienen ordenados (lo normal cuando los flatlanders vienen ordenados por x) no se hace nada,
+/// si son pocos se usa un sort por comparacion y si son muchos un radix sort
 fn ordenar_por_inicio(intervalos: &mut [Intervalo]) {
-    // me lo hace poner clippy, queria hacerlo como en la clase recibiendo un vector
-    let n = intervalos.len();
-    for i in 0..n {
-        let mut min = i;
-        for j in i + 1..n {
-            if intervalos[j].inicio < intervalos[min].inicio {
-                min = j;
-            }
+    if esta_ordenado(intervalos) {
+        return;
+    }
+    if intervalos.len() >= UMBRAL_RADIX {
+        ordenar_por_radix(intervalos);
+    } else {
+        ordenar_por_comparacion(intervalos);
+    }
+}
+
+/// Devuelve true si los intervalos ya estan ordenados por inicio
+///
+/// Es O(n) asi que sale mucho mas barato que ordenar de nuevo
+fn esta_ordenado(intervalos: &[Intervalo]) -> bool {
+    intervalos
+        .windows(2)
+        .all(|par| par[0].inicio.total_cmp(&par[1].inicio).is_le())
+}
+
+/// Ordena con el sort de la biblioteca estandar
+///
+/// Uso total_cmp para que un NaN no rompa el orden, quedan todos al final
+fn ordenar_por_comparacion(intervalos: &mut [Intervalo]) {
+    intervalos.sort_unstable_by(|a, b| a.inicio.total_cmp(&b.inicio));
+}
+
+/// Transforma los bits de un f64 en un u64 que se ordena igual que con total_cmp
+///
+/// A los positivos se les prende el bit de signo y a los negativos se les dan vuelta todos los bits
+fn clave_de_orden(valor: f64) -> u64 {
+    let bits = valor.to_bits();
+    if bits >> 63 == 1 {
+        !bits
+    } else {
+        bits | (1 << 63)
+    }
+}
+
+/// Radix sort LSD sobre la clave de orden del inicio, de a un byte por pasada
+///
+/// Es estable y O(n) por pasada, las pasadas donde todos caen en el mismo balde se saltean
+fn ordenar_por_radix(intervalos: &mut [Intervalo]) {
+    let mut actual: Vec<(u64, Intervalo)> = intervalos
+        .iter()
+        .map(|intervalo| (clave_de_orden(intervalo.inicio), *intervalo))
+        .collect();
+    let mut auxiliar = actual.clone();
+
+    for pasada in 0..8 {
+        let desplazamiento = pasada * 8;
+        let mut conteo = [0usize; 256];
+        for (clave, _) in &actual {
+            conteo[((clave >> desplazamiento) & 0xFF) as usize] += 1;
         }
-        if min != i {
-            intervalos.swap(i, min);
+        if conteo.contains(&actual.len()) {
+            continue;
         }
+
+        let mut posiciones = [0usize; 256];
+        let mut acumulado = 0;
+        for (posicion, cantidad) in posiciones.iter_mut().zip(conteo) {
+            *posicion = acumulado;
+            acumulado += cantidad;
+        }
+
+        for elemento in &actual {
+            let balde = ((elemento.0 >> desplazamiento) & 0xFF) as usize;
+            auxiliar[posiciones[balde]] = *elemento;
+            posiciones[balde] += 1;
+        }
+        std::mem::swap(&mut actual, &mut auxiliar);
+    }
+
+    for (destino, (_, intervalo)) in intervalos.iter_mut().zip(actual) {
+        *destino = intervalo;
     }
 }
 
-/// Devuelve la longitud de la union de los intervalos
+/// Devuelve las regiones disjuntas de la union, ordenadas de izquierda a derecha
 ///
-/// Recibe un vector con los mismos
-pub fn longitud_union(mut intervalos: Vec<Intervalo>) -> f64 {
-    let n = intervalos.len();
-    if n == 0 {
-        return 0.0;
+/// Recibe un vector con los intervalos, los que se tocan en un punto quedan en la misma region
+pub fn union_de_intervalos(mut intervalos: Vec<Intervalo>) -> Vec<Intervalo> {
+    let mut union = Vec::new();
+    if intervalos.is_empty() {
+        return union;
     }
 
     ordenar_por_inicio(&mut intervalos);
 
-    let mut longitud_total = 0.0;
-    let mut actual = Intervalo::nuevo(intervalos[0].inicio, intervalos[0].fin);
+    let mut actual = intervalos[0];
 
-    for intervalo_iterado in intervalos.iter().take(n).skip(1) {
+    for intervalo_iterado in intervalos.iter().skip(1) {
         if intervalo_iterado.inicio <= actual.fin {
             if intervalo_iterado.fin > actual.fin {
                 actual.fin = intervalo_iterado.fin;
             }
         } else {
-            longitud_total += actual.fin - actual.inicio;
-            actual = Intervalo::nuevo(intervalo_iterado.inicio, intervalo_iterado.fin);
+            union.push(actual);
+            actual = *intervalo_iterado;
         }
     }
-    longitud_total += actual.fin - actual.inicio;
-    longitud_total
+    union.push(actual);
+    union
+}
+
+/// Devuelve la longitud de la union de los intervalos
+///
+/// Recibe un vector con los mismos
+pub fn longitud_union(intervalos: Vec<Intervalo>) -> f64 {
+    longitud_total(&union_de_intervalos(intervalos))
+}
+
+/// Suma las longitudes de una union ya calculada
+///
+/// Arranca de 0.0 y no de sum(), que para f64 empieza en -0.0 y sin intervalos imprimiria -0
+pub fn longitud_total(union: &[Intervalo]) -> f64 {
+    union
+        .iter()
+        .fold(0.0, |total, intervalo| total + intervalo.longitud())
+}
+
+/// Devuelve los huecos iluminados que deja la union dentro de la ventana dada
+///
+/// Recibe la union ya calculada (disjunta y ordenada) y no devuelve huecos de longitud cero
+pub fn huecos_iluminados(union: &[Intervalo], ventana: &Intervalo) -> Vec<Intervalo> {
+    let mut huecos = Vec::new();
+    let mut cursor = ventana.inicio;
+
+    for region in union {
+        if region.fin <= cursor {
+            continue;
+        }
+        if region.inicio >= ventana.fin {
+            break;
+        }
+        if region.inicio > cursor {
+            huecos.push(Intervalo::nuevo(cursor, region.inicio));
+        }
+        cursor = region.fin;
+    }
+    if cursor < ventana.fin {
+        huecos.push(Intervalo::nuevo(cursor, ventana.fin));
+    }
+    huecos
 }
 
 /// Devuelve un vector con las sombras de los flatlanders
 ///
-/// Recibe el vector con los mismos y el angulo
+/// Recibe el vector con los mismos y el angulo, entre 0 y 180 grados
+///
+/// Hasta 90 las sombras van a la derecha de x y pasando 90 a la izquierda. El angulo no se valida,
+/// con 0 o 180 las sombras salen infinitas o enormes, para esos casos esta sombras_con_angulo_valido
 pub fn sombras_desde_flatlanders(flatlanders: &[Flatlander], angulo_grados: f64) -> Vec<Intervalo> {
     let mut vector = Vec::with_capacity(flatlanders.len());
     for flatlander in flatlanders {
-        let largo = longitud_de_sombra(flatlander.h, angulo_grados);
-        vector.push(Intervalo::nuevo(flatlander.x, flatlander.x + largo));
+        vector.push(sombra_de_flatlander(flatlander, angulo_grados));
     }
     vector
 }
 
+/// Devuelve la sombra de un solo flatlander, con el mismo criterio que la de arriba
+pub fn sombra_de_flatlander(flatlander: &Flatlander, angulo_grados: f64) -> Intervalo {
+    let desplazamiento = desplazamiento_de_sombra(flatlander.h, angulo_grados);
+    Intervalo::nuevo(flatlander.x, flatlander.x + desplazamiento)
+}
+
+/// Igual que sombras_desde_flatlanders pero validando el angulo
+///
+/// Devuelve None si el angulo no esta estrictamente entre 0 y 180, donde la sombra seria infinita
+pub fn sombras_con_angulo_valido(
+    flatlanders: &[Flatlander],
+    angulo_grados: f64,
+) -> Option<Vec<Intervalo>> {
+    angulo_de_sol_valido(angulo_grados)
+        .then(|| sombras_desde_flatlanders(flatlanders, angulo_grados))
+}
+
+/// Igual que sombras_desde_flatlanders pero perezoso, para encadenarlo con filtros sin armar vectores
+pub fn sombras_desde_iterador(
+    flatlanders: impl IntoIterator<Item = Flatlander>,
+    angulo_grados: f64,
+) -> impl Iterator<Item = Intervalo> {
+    flatlanders
+        .into_iter()
+        .map(move |flatlander| sombra_de_flatlander(&flatlander, angulo_grados))
+}
+
+/// Igual que sombras_desde_flatlanders pero con la elevacion del sol y la direccion explicita
+///
+/// Devuelve None si la elevacion no esta entre 0 y 90 o da una sombra infinita
+pub fn sombras_con_direccion(
+    flatlanders: &[Flatlander],
+    elevacion: f64,
+    direccion: Direccion,
+) -> Option<Vec<Intervalo>> {
+    if !(elevacion > 0.0 && elevacion <= 90.0) {
+        return None;
+    }
+    sombras_con_angulo_valido(flatlanders, angulo_desde_elevacion(elevacion, direccion))
+}
+
+/// El ordenamiento por seleccion que habia antes, lo dejo para comparar las estrategias nuevas
+#[cfg(test)]
+fn ordenar_por_seleccion(intervalos: &mut [Intervalo]) {
+    let n = intervalos.len();
+    for i in 0..n {
+        let mut min = i;
+        for j in i + 1..n {
+            if intervalos[j].inicio < intervalos[min].inicio {
+                min = j;
+            }
+        }
+        if min != i {
+            intervalos.swap(i, min);
+        }
+    }
+}
+
+/// Genera intervalos pseudoaleatorios de largo 10 entre -300000 y 300000
+#[cfg(test)]
+fn intervalos_pseudoaleatorios(cantidad: usize, semilla: u64) -> Vec<Intervalo> {
+    let mut generador = crate::pruebas::Generador::nuevo(semilla);
+    (0..cantidad)
+        .map(|_| {
+            let inicio = generador.siguiente_f64() * 600_000.0 - 300_000.0;
+            Intervalo::nuevo(inicio, inicio + 10.0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+fn inicios(intervalos: &[Intervalo]) -> Vec<f64> {
+    intervalos
+        .iter()
+        .map(|intervalo| intervalo.inicio)
+        .collect()
+}
+
 #[test]
 fn ordena_por_inicio_funciona() {
     let mut vector = vec![
@@ -93,11 +282,159 @@ fn union_de_intervalos_separados_y_juntos() {
 
 #[test]
 fn sombras_en_45_grados() {
-    let flatlanders = vec![
-        Flatlander { x: 0.0, h: 10.0 },
-        Flatlander { x: 5.0, h: 10.0 },
-    ];
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(5.0, 10.0)];
     let sombras = sombras_desde_flatlanders(&flatlanders, 45.0);
     let total = longitud_union(sombras);
     assert!((total - 15.0).abs() < 1e-12);
 }
+
+#[test]
+fn ordenar_por_comparacion_coincide_con_seleccion() {
+    let mut esperado = intervalos_pseudoaleatorios(500, 7);
+    let mut obtenido = esperado.clone();
+    ordenar_por_seleccion(&mut esperado);
+    ordenar_por_comparacion(&mut obtenido);
+    assert_eq!(inicios(&obtenido), inicios(&esperado));
+}
+
+#[test]
+fn ordenar_por_radix_coincide_con_seleccion() {
+    let mut esperado = intervalos_pseudoaleatorios(2000, 11);
+    esperado.push(Intervalo::nuevo(-0.0, 1.0));
+    esperado.push(Intervalo::nuevo(0.0, 1.0));
+    let mut obtenido = esperado.clone();
+    ordenar_por_seleccion(&mut esperado);
+    ordenar_por_radix(&mut obtenido);
+    assert_eq!(inicios(&obtenido), inicios(&esperado));
+}
+
+#[test]
+fn ordenar_por_inicio_con_muchos_usa_radix_y_ordena() {
+    let mut vector = intervalos_pseudoaleatorios(UMBRAL_RADIX * 2, 3);
+    ordenar_por_inicio(&mut vector);
+    assert!(esta_ordenado(&vector));
+}
+
+#[test]
+fn ya_ordenado_no_se_modifica() {
+    let flatlanders: Vec<Flatlander> = (0..10).map(|i| Flatlander::nuevo(i as f64, 5.0)).collect();
+    let mut sombras = sombras_desde_flatlanders(&flatlanders, 45.0);
+    assert!(esta_ordenado(&sombras));
+    let antes = sombras.clone();
+    ordenar_por_inicio(&mut sombras);
+    assert_eq!(sombras, antes);
+}
+
+#[test]
+fn nan_queda_al_final_sin_romper_el_orden() {
+    let mut vector = vec![
+        Intervalo {
+            inicio: f64::NAN,
+            fin: f64::NAN,
+        },
+        Intervalo::nuevo(3.0, 4.0),
+        Intervalo::nuevo(1.0, 2.0),
+    ];
+    let mut con_radix = vector.clone();
+    ordenar_por_comparacion(&mut vector);
+    ordenar_por_radix(&mut con_radix);
+    assert_eq!(vector[0].inicio, 1.0);
+    assert_eq!(vector[1].inicio, 3.0);
+    assert!(vector[2].inicio.is_nan());
+    assert_eq!(con_radix[0].inicio, 1.0);
+    assert!(con_radix[2].inicio.is_nan());
+}
+
+#[test]
+fn union_devuelve_regiones_disjuntas_ordenadas() {
+    let vector = vec![
+        Intervalo::nuevo(10.0, 12.0),
+        Intervalo::nuevo(3.0, 5.0),
+        Intervalo::nuevo(5.0, 8.0),
+        Intervalo::nuevo(11.0, 14.0),
+    ];
+    let union = union_de_intervalos(vector);
+    assert_eq!(
+        union,
+        vec![Intervalo::nuevo(3.0, 8.0), Intervalo::nuevo(10.0, 14.0)]
+    );
+}
+
+#[test]
+fn union_vacia_no_tiene_regiones() {
+    assert!(union_de_intervalos(Vec::new()).is_empty());
+}
+
+#[test]
+fn union_vacia_da_cero_positivo() {
+    let total = longitud_union(Vec::new());
+    assert_eq!(total, 0.0);
+    assert!(total.is_sign_positive());
+    assert!(longitud_total(&[]).is_sign_positive());
+}
+
+#[test]
+fn huecos_dentro_de_la_ventana() {
+    let union = vec![Intervalo::nuevo(3.0, 8.0), Intervalo::nuevo(10.0, 14.0)];
+    let huecos = huecos_iluminados(&union, &Intervalo::nuevo(0.0, 20.0));
+    assert_eq!(
+        huecos,
+        vec![
+            Intervalo::nuevo(0.0, 3.0),
+            Intervalo::nuevo(8.0, 10.0),
+            Intervalo::nuevo(14.0, 20.0),
+        ]
+    );
+}
+
+#[test]
+fn huecos_con_ventana_que_corta_las_regiones() {
+    let union = vec![Intervalo::nuevo(3.0, 8.0), Intervalo::nuevo(10.0, 14.0)];
+    let huecos = huecos_iluminados(&union, &Intervalo::nuevo(5.0, 12.0));
+    assert_eq!(huecos, vec![Intervalo::nuevo(8.0, 10.0)]);
+}
+
+#[test]
+fn huecos_con_ventana_totalmente_a_la_sombra() {
+    let union = vec![Intervalo::nuevo(0.0, 10.0)];
+    let huecos = huecos_iluminados(&union, &Intervalo::nuevo(2.0, 4.0));
+    assert!(huecos.is_empty());
+}
+
+#[test]
+fn sombras_a_la_izquierda_pasando_90() {
+    let flatlanders = vec![Flatlander::nuevo(10.0, 5.0)];
+    let sombras = sombras_desde_flatlanders(&flatlanders, 135.0);
+    assert!((sombras[0].inicio - 5.0).abs() < 1e-12);
+    assert_eq!(sombras[0].fin, 10.0);
+}
+
+#[test]
+fn sombras_con_direccion_izquierda_espejan_las_de_la_derecha() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0), Flatlander::nuevo(5.0, 10.0)];
+    let derecha = sombras_con_direccion(&flatlanders, 45.0, Direccion::Derecha).unwrap();
+    let izquierda = sombras_con_direccion(&flatlanders, 45.0, Direccion::Izquierda).unwrap();
+    assert!((izquierda[0].inicio + 10.0).abs() < 1e-12);
+    assert!((longitud_union(derecha) - longitud_union(izquierda)).abs() < 1e-12);
+}
+
+#[test]
+fn sombras_con_direccion_rechaza_elevacion_degenerada() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0)];
+    assert!(sombras_con_direccion(&flatlanders, 0.0, Direccion::Derecha).is_none());
+    assert!(sombras_con_direccion(&flatlanders, 95.0, Direccion::Izquierda).is_none());
+    let al_mediodia = sombras_con_direccion(&flatlanders, 90.0, Direccion::Derecha).unwrap();
+    assert_eq!(al_mediodia[0].longitud(), 0.0);
+}
+
+#[test]
+fn sombras_con_angulo_valido_rechaza_los_extremos() {
+    let flatlanders = vec![Flatlander::nuevo(0.0, 10.0)];
+    assert!(sombras_con_angulo_valido(&flatlanders, 0.0).is_none());
+    assert!(sombras_con_angulo_valido(&flatlanders, 180.0).is_none());
+    assert!(sombras_con_angulo_valido(&flatlanders, f64::NAN).is_none());
+    let cerca_de_0 = sombras_con_angulo_valido(&flatlanders, 0.5).unwrap();
+    let cerca_de_180 = sombras_con_angulo_valido(&flatlanders, 179.5).unwrap();
+    assert!(cerca_de_0[0].fin.is_finite() && cerca_de_0[0].inicio == 0.0);
+    assert!(cerca_de_180[0].inicio.is_finite() && cerca_de_180[0].fin == 0.0);
+}
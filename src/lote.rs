@@ -0,0 +1,281 @@
+/// Archivo para parsear varias escenas seguidas en la misma entrada
+///
+/// Cada caso se parsea con parsear_encabezado y parsear_flatlanders, igual que una escena sola.
+/// Esas cuentan las lineas desde el principio de la escena, asi que los errores se corren a la
+/// linea que ocupan en el lote
+use crate::flatlander::Flatlander;
+use crate::parseador::{ErrorParseo, Limites, parsear_encabezado, parsear_flatlanders};
+
+/// Como se sabe cuantas escenas hay en la entrada
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatoDeLote {
+    /// La primera linea tiene la cantidad de casos T
+    ConCantidad,
+    /// Las escenas van una atras de otra hasta que se termina la entrada
+    HastaElFinal,
+}
+
+/// Resultado de cada caso del lote: el angulo y los flatlanders, o el error de ese caso
+pub type ResultadoDeCaso = Result<(f64, Vec<Flatlander>), ErrorParseo>;
+
+/// Parsea todas las escenas de la entrada, cada una con su propio resultado
+///
+/// Las lineas en blanco entre escenas se ignoran. Si falla un flatlander se saltean las lineas que
+/// le faltaban a esa escena y se sigue con la proxima. Si falla un encabezado no se sabe cuantas
+/// lineas tenia la escena, asi que se sigue desde la proxima linea que sea un encabezado valido
+///
+/// Con la cantidad T siempre se devuelven T casos, si la entrada se termina antes los que faltan
+/// son de linea faltante
+///
+/// Solo devuelve Err si en el formato con cantidad la primera linea no sirve
+pub fn parsear_lote(
+    entrada: &str,
+    formato: FormatoDeLote,
+    limites: Option<&Limites>,
+) -> Result<Vec<ResultadoDeCaso>, ErrorParseo> {
+    let limites = limites.copied().unwrap_or_default();
+    let mut lineas = entrada.lines();
+    let mut numero_linea = 0;
+
+    let cantidad = match formato {
+        FormatoDeLote::ConCantidad => Some(parsear_cantidad(&mut lineas, &mut numero_linea)?),
+        FormatoDeLote::HastaElFinal => None,
+    };
+
+    let mut casos = Vec::new();
+    // encabezado que ya se encontro al buscar donde seguir despues de uno que fallo
+    let mut siguiente_encabezado = None;
+    while cantidad.is_none_or(|t| casos.len() < t) {
+        let linea = siguiente_encabezado
+            .take()
+            .or_else(|| siguiente_linea_no_vacia(&mut lineas, &mut numero_linea));
+        let Some(linea) = linea else {
+            while cantidad.is_some_and(|t| casos.len() < t) {
+                casos.push(Err(ErrorParseo::LineaFaltante {
+                    linea: numero_linea + 1,
+                }));
+            }
+            break;
+        };
+        match parsear_encabezado(Some(linea), Some(&limites)) {
+            Ok((angulo, n)) => casos.push(parsear_caso(
+                &mut lineas,
+                &mut numero_linea,
+                angulo,
+                n,
+                &limites,
+            )),
+            Err(mut error) => {
+                if let Some(linea) = linea_del_error(&mut error) {
+                    *linea += numero_linea - 1;
+                }
+                casos.push(Err(error));
+                siguiente_encabezado = buscar_encabezado(&mut lineas, &mut numero_linea, &limites);
+            }
+        }
+    }
+    Ok(casos)
+}
+
+/// Funcion interna que saltea lineas hasta la primera que se puede parsear como encabezado
+///
+/// Un flatlander cuyos valores tambien sirven de angulo y n se toma como encabezado, no hay forma
+/// de distinguirlos
+fn buscar_encabezado<'a>(
+    lineas: &mut std::str::Lines<'a>,
+    numero_linea: &mut usize,
+    limites: &Limites,
+) -> Option<&'a str> {
+    lineas.by_ref().find(|linea| {
+        *numero_linea += 1;
+        parsear_encabezado(Some(linea), Some(limites)).is_ok()
+    })
+}
+
+/// Funcion interna que parsea la linea con la cantidad de casos
+fn parsear_cantidad(
+    lineas: &mut std::str::Lines<'_>,
+    numero_linea: &mut usize,
+) -> Result<usize, ErrorParseo> {
+    let linea =
+        siguiente_linea_no_vacia(lineas, numero_linea).ok_or(ErrorParseo::LineaFaltante {
+            linea: *numero_linea + 1,
+        })?;
+    let token = linea.trim();
+    token
+        .parse::<usize>()
+        .map_err(|_| ErrorParseo::NumeroInvalido {
+            linea: *numero_linea,
+            columna: linea.len() - linea.trim_start().len() + 1,
+            token: token.to_string(),
+        })
+}
+
+/// Funcion interna que parsea los n flatlanders de un caso y, si alguno falla, saltea el resto
+///
+/// numero_linea es la del encabezado del caso y queda en la ultima linea que se leyo
+fn parsear_caso(
+    lineas: &mut std::str::Lines<'_>,
+    numero_linea: &mut usize,
+    angulo: f64,
+    n: usize,
+    limites: &Limites,
+) -> ResultadoDeCaso {
+    let encabezado = *numero_linea;
+    let fin_del_caso = encabezado + n;
+    match parsear_flatlanders(lineas, n, Some(limites)) {
+        Ok(flatlanders) => {
+            *numero_linea = fin_del_caso;
+            Ok((angulo, flatlanders))
+        }
+        Err(mut error) => {
+            let se_termino_la_entrada = matches!(error, ErrorParseo::LineaFaltante { .. });
+            if let Some(linea) = linea_del_error(&mut error) {
+                // para parsear_flatlanders el encabezado es la linea 1
+                *linea += encabezado - 1;
+                // la linea con el error ya se consumio, la que falta no
+                *numero_linea = if se_termino_la_entrada {
+                    *linea - 1
+                } else {
+                    *linea
+                };
+            }
+            while *numero_linea < fin_del_caso && lineas.next().is_some() {
+                *numero_linea += 1;
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Funcion interna que devuelve la linea del error para correrla, los de IO no tienen
+fn linea_del_error(error: &mut ErrorParseo) -> Option<&mut usize> {
+    match error {
+        ErrorParseo::Io { .. } => None,
+        ErrorParseo::LineaFaltante { linea }
+        | ErrorParseo::ValorFaltante { linea, .. }
+        | ErrorParseo::NumeroInvalido { linea, .. }
+        | ErrorParseo::FueraDeRango { linea, .. }
+        | ErrorParseo::DatosSobrantes { linea, .. }
+        | ErrorParseo::DirectivaInvalida { linea, .. }
+        | ErrorParseo::DirectivaFaltante { linea, .. }
+        | ErrorParseo::FormatoInvalido { linea, .. }
+        | ErrorParseo::AnguloDegenerado { linea, .. } => Some(linea),
+    }
+}
+
+/// Funcion interna que devuelve la siguiente linea que no esta en blanco, contando las lineas
+fn siguiente_linea_no_vacia<'a>(
+    lineas: &mut std::str::Lines<'a>,
+    numero_linea: &mut usize,
+) -> Option<&'a str> {
+    for linea in lineas.by_ref() {
+        *numero_linea += 1;
+        if !linea.trim().is_empty() {
+            return Some(linea);
+        }
+    }
+    None
+}
+
+#[test]
+fn lote_con_cantidad() {
+    let entrada = "2\n45 2\n0 10\n5 10\n30 3\n50 150\n0 100\n100 200\n";
+    let casos = parsear_lote(entrada, FormatoDeLote::ConCantidad, None).unwrap();
+    assert_eq!(casos.len(), 2);
+    assert_eq!(casos[0].as_ref().unwrap().1.len(), 2);
+    assert_eq!(casos[1].as_ref().unwrap().0, 30.0);
+}
+
+#[test]
+fn lote_hasta_el_final_con_lineas_en_blanco() {
+    let entrada = "45 1\n0 10\n\n\n45 1\n3 4\n\n";
+    let casos = parsear_lote(entrada, FormatoDeLote::HastaElFinal, None).unwrap();
+    assert_eq!(casos.len(), 2);
+    assert_eq!(casos[1].as_ref().unwrap().1[0], Flatlander::nuevo(3.0, 4.0));
+}
+
+#[test]
+fn un_caso_malo_no_corta_el_lote() {
+    let entrada = "45 3\n0 10\nx 1\n2 2\n45 1\n0 10\n";
+    let casos = parsear_lote(entrada, FormatoDeLote::HastaElFinal, None).unwrap();
+    assert_eq!(casos.len(), 2);
+    assert_eq!(
+        casos[0],
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 3,
+            columna: 1,
+            token: "x".to_string(),
+        })
+    );
+    assert!(casos[1].is_ok());
+}
+
+#[test]
+fn encabezado_malo_sigue_en_el_proximo_encabezado() {
+    let entrada = "45 1\n0 10\n99 1\n0 10\n45 1\n0 10\n";
+    let casos = parsear_lote(entrada, FormatoDeLote::HastaElFinal, None).unwrap();
+    assert_eq!(casos.len(), 3);
+    assert!(matches!(
+        casos[1],
+        Err(ErrorParseo::FueraDeRango { linea: 3, .. })
+    ));
+    assert_eq!(casos[2], Ok((45.0, vec![Flatlander::nuevo(0.0, 10.0)])));
+}
+
+#[test]
+fn con_cantidad_siempre_devuelve_t_casos() {
+    let entrada = "3\n45 x\n0 10\n45 1\n0 10\n45 1\n0 10\n";
+    let casos = parsear_lote(entrada, FormatoDeLote::ConCantidad, None).unwrap();
+    assert_eq!(casos.len(), 3);
+    assert!(matches!(
+        casos[0],
+        Err(ErrorParseo::NumeroInvalido { linea: 2, .. })
+    ));
+    assert!(casos[1].is_ok() && casos[2].is_ok());
+    let sin_encabezados = parsear_lote("2\n45 x\n0 10\n", FormatoDeLote::ConCantidad, None);
+    assert_eq!(
+        sin_encabezados.unwrap()[1],
+        Err(ErrorParseo::LineaFaltante { linea: 4 })
+    );
+}
+
+#[test]
+fn faltan_casos_segun_la_cantidad() {
+    let entrada = "3\n45 1\n0 10\n";
+    let casos = parsear_lote(entrada, FormatoDeLote::ConCantidad, None).unwrap();
+    assert_eq!(casos.len(), 3);
+    assert_eq!(casos[1], Err(ErrorParseo::LineaFaltante { linea: 4 }));
+    assert_eq!(casos[2], Err(ErrorParseo::LineaFaltante { linea: 4 }));
+    let casos = parsear_lote("2\n45 3\n0 10\n", FormatoDeLote::ConCantidad, None).unwrap();
+    assert_eq!(casos[0], Err(ErrorParseo::LineaFaltante { linea: 4 }));
+    assert_eq!(casos[1], Err(ErrorParseo::LineaFaltante { linea: 4 }));
+}
+
+#[test]
+fn un_caso_se_parsea_igual_que_una_escena_sola() {
+    let entradas = [
+        "45 2\n0 10\n5 10\n",
+        "45 2\n0 10\n",
+        "45 1\n0 a\n",
+        "45 1\n300001 10\n",
+        "5 2\n0 10\n0 10\n",
+        "45\n",
+    ];
+    for entrada in entradas {
+        let mut lineas = entrada.lines();
+        let escena = parsear_encabezado(lineas.next(), None)
+            .and_then(|(angulo, n)| Ok((angulo, parsear_flatlanders(&mut lineas, n, None)?)));
+        let casos = parsear_lote(entrada, FormatoDeLote::HastaElFinal, None).unwrap();
+        assert_eq!(casos[0], escena, "entrada {entrada:?}");
+    }
+}
+
+#[test]
+fn cantidad_invalida() {
+    let resultado = parsear_lote("dos\n", FormatoDeLote::ConCantidad, None);
+    assert!(matches!(
+        resultado,
+        Err(ErrorParseo::NumeroInvalido { linea: 1, .. })
+    ));
+}
@@ -1,30 +1,645 @@
 use ejercicio_individual_1_santiagosielecki::{
-    longitud_union::{longitud_union, sombras_desde_flatlanders},
-    parseador::{leer_entrada, parsear_encabezado, parsear_flatlanders},
+    angulo_inverso::{ResultadoInverso, angulo_para_longitud},
+    animacion::{
+        SEGUNDOS_POR_CUADRO, barrido_a_svg_animado, cuadros_del_barrido, nombre_de_cuadro,
+    },
+    argumentos::{
+        Configuracion, ErrorDeArgumento, SALIDA_IO, SALIDA_USO, codigo_de_salida,
+        parsear_argumentos,
+    },
+    atribucion::atribuir_sombra,
+    barrido::{EscenaOrdenada, angulos_en_rango},
+    cobertura::{perfil_de_profundidad, profundidad_maxima},
+    diagnostico::{FormatoDeDiagnosticos, diagnosticar, diagnostico_a_json, diagnostico_a_texto},
+    dibujo_svg::{OpcionesDeSvg, escena_a_svg},
+    dibujo_terminal::{
+        ANCHO_POR_DEFECTO, JuegoDeCaracteres, OpcionesDeTerminal, curva_de_cobertura,
+        escena_a_texto,
+    },
+    escena_anotada::parsear_escena_anotada_con_opciones,
+    flatlander::Flatlander,
+    formato_csv::{parsear_csv_con_opciones, resumen_a_csv},
+    formato_json::{parsear_json, resumen_a_json},
+    formatos::{FormatoDeEntrada, FormatoDeSalida, ResumenDeEscena},
+    geometria::angulo_de_sol_valido,
+    intervalo::Intervalo,
+    lector::LectorDeEscena,
+    longitud_union::{
+        huecos_iluminados, longitud_total, longitud_union, sombras_desde_flatlanders,
+        union_de_intervalos,
+    },
+    lote::{FormatoDeLote, parsear_lote},
+    mapa_de_calor::{MapaDeSombra, mapa_a_pgm, mapa_a_ppm},
+    mensajes::{
+        Idioma, advertencia_localizada, error_de_argumento_localizado, error_localizado,
+        errores_en_la_entrada, idioma_del_entorno,
+    },
+    numeros::{FormatoDeNumeros, SeparadorDecimal},
+    parseador::{CategoriaDeError, ErrorParseo, OpcionesDeParseo, leer_entrada_desde},
 };
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
 
-/// Centro de la ejecucion donde se hace la mezcla de todo
+/// Por que termino mal la ejecucion, cada caso tiene su codigo de salida
+enum Fallo {
+    /// Los argumentos no sirven
+    Argumento(ErrorDeArgumento),
+    /// La entrada no se pudo leer o parsear
+    Parseo(ErrorParseo),
+    /// No se pudo escribir la salida
+    Salida(io::Error),
+    /// Se pidieron los diagnosticos y la entrada tiene errores
+    Diagnosticos {
+        /// Cuantos errores se encontraron
+        cantidad: usize,
+        /// Categoria del primero, que decide el codigo de salida
+        categoria: CategoriaDeError,
+    },
+}
+
+impl Fallo {
+    fn codigo(&self) -> u8 {
+        match self {
+            Fallo::Argumento(_) => SALIDA_USO,
+            Fallo::Parseo(error) => codigo_de_salida(error.categoria()),
+            Fallo::Salida(_) => SALIDA_IO,
+            Fallo::Diagnosticos { categoria, .. } => codigo_de_salida(*categoria),
+        }
+    }
+
+    /// Mensaje para la salida de errores, en espanol los de parseo se muestran igual que siempre
+    fn mensaje(&self, idioma: Idioma) -> String {
+        match self {
+            Fallo::Argumento(error) => error_de_argumento_localizado(error, idioma),
+            Fallo::Parseo(error) => error_localizado(error, idioma),
+            Fallo::Salida(error) => format!("{}: {error}", idioma.catalogo().salida_fallida),
+            Fallo::Diagnosticos { cantidad, .. } => errores_en_la_entrada(*cantidad, idioma),
+        }
+    }
+}
+
+impl From<ErrorDeArgumento> for Fallo {
+    fn from(error: ErrorDeArgumento) -> Self {
+        Fallo::Argumento(error)
+    }
+}
+
+impl From<ErrorParseo> for Fallo {
+    fn from(error: ErrorParseo) -> Self {
+        Fallo::Parseo(error)
+    }
+}
+
+impl From<io::Error> for Fallo {
+    fn from(error: io::Error) -> Self {
+        Fallo::Salida(error)
+    }
+}
+
+/// Abre la entrada pedida, o la entrada estandar si no se pidio ninguna
+fn abrir_entrada(ruta: Option<&str>) -> Result<Box<dyn BufRead>, ErrorParseo> {
+    match ruta {
+        None => Ok(Box::new(io::stdin().lock())),
+        Some(ruta) => File::open(ruta)
+            .map(|archivo| Box::new(BufReader::new(archivo)) as Box<dyn BufRead>)
+            .map_err(|e| ErrorParseo::Io {
+                mensaje: e.to_string(),
+            }),
+    }
+}
+
+/// Abre la salida pedida, o la salida estandar si no se pidio ninguna
+fn abrir_salida(ruta: Option<&str>) -> io::Result<Box<dyn Write>> {
+    match ruta {
+        None => Ok(Box::new(io::stdout().lock())),
+        Some(ruta) => Ok(Box::new(BufWriter::new(File::create(ruta)?))),
+    }
+}
+
+/// Opciones de parseo que salen de los argumentos
+fn opciones_de_parseo(configuracion: &Configuracion) -> OpcionesDeParseo {
+    OpcionesDeParseo {
+        numeros_rapidos: true,
+        modo: configuracion.modo,
+        decimal: configuracion.numeros.separador,
+    }
+}
+
+/// Lee y parsea la entrada y devuelve el angulo y los flatlanders
+///
+/// Si no se dice el formato se adivina mirando el principio de la entrada. El clasico se lee de a
+/// una linea con el parseo rapido de numeros, que da los mismos resultados que el de siempre, y
+/// las advertencias del modo permisivo se imprimen en la salida de errores. Los otros formatos se
+/// leen enteros
+///
+/// El angulo dado por argumento le gana al de la entrada, y en CSV o en una lista de JSON es la
+/// unica forma de darlo
+fn ejecutar(
+    mut entrada: Box<dyn BufRead>,
+    configuracion: &Configuracion,
+    idioma: Idioma,
+) -> Result<(f64, Vec<Flatlander>), ErrorParseo> {
+    let limites = Some(&configuracion.limites);
+    let opciones = opciones_de_parseo(configuracion);
+    let principio = entrada.fill_buf().map_err(|e| ErrorParseo::Io {
+        mensaje: e.to_string(),
+    })?;
+    let formato = configuracion
+        .formato_de_entrada
+        .unwrap_or_else(|| FormatoDeEntrada::detectar(principio));
+    let (angulo_de_la_entrada, flatlanders) = match formato {
+        FormatoDeEntrada::Clasico => {
+            let mut lector = LectorDeEscena::con_opciones(entrada, limites, opciones)?;
+            let flatlanders = lector.by_ref().collect::<Result<Vec<_>, _>>()?;
+            for advertencia in lector.advertencias() {
+                eprintln!(
+                    "{}: {}",
+                    idioma.catalogo().advertencia,
+                    advertencia_localizada(advertencia, idioma)
+                );
+            }
+            (Some(lector.angulo()), flatlanders)
+        }
+        FormatoDeEntrada::Anotado => {
+            let entrada = leer_entrada_desde(entrada)?;
+            let escena = parsear_escena_anotada_con_opciones(&entrada, limites, opciones)?;
+            (Some(escena.angulo), escena.flatlanders)
+        }
+        FormatoDeEntrada::Csv => (
+            None,
+            parsear_csv_con_opciones(&leer_entrada_desde(entrada)?, limites, opciones)?,
+        ),
+        FormatoDeEntrada::Json => parsear_json(&leer_entrada_desde(entrada)?, limites)?,
+    };
+    let angulo =
+        configuracion
+            .angulo
+            .or(angulo_de_la_entrada)
+            .ok_or(ErrorParseo::DirectivaFaltante {
+                linea: 1,
+                clave: "angulo".to_string(),
+            })?;
+    Ok((angulo, flatlanders))
+}
+
+/// Lee toda la entrada como un lote de escenas y escribe una linea por caso
 ///
-/// Imprime el resultado final
-fn main() {
-    let entrada = match leer_entrada() {
-        Some(e) => e,
-        None => return,
+/// Cada caso escribe su total o su error en la salida, asi cada linea corresponde a un caso. El
+/// lote se lee siempre con punto decimal, asi que tambien se escribe con punto
+fn ejecutar_lote(
+    entrada: Box<dyn BufRead>,
+    salida: &mut dyn Write,
+    formato: FormatoDeLote,
+    configuracion: &Configuracion,
+    idioma: Idioma,
+) -> Result<(), Fallo> {
+    let numeros = FormatoDeNumeros {
+        separador: SeparadorDecimal::Punto,
+        ..configuracion.numeros
     };
+    let entrada = leer_entrada_desde(entrada)?;
+    for caso in parsear_lote(&entrada, formato, Some(&configuracion.limites))? {
+        match caso {
+            Ok((angulo, flatlanders)) => {
+                let total = longitud_union(sombras_desde_flatlanders(&flatlanders, angulo));
+                writeln!(salida, "{}", numeros.formatear(total))?;
+            }
+            Err(error) => writeln!(
+                salida,
+                "{}: {}",
+                idioma.catalogo().error,
+                error_localizado(&error, idioma)
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Lee toda la entrada y escribe cada uno de sus errores en vez de calcular la sombra
+///
+/// Si hay errores la ejecucion termina con el codigo de la categoria del primero
+fn ejecutar_diagnosticos(
+    entrada: Box<dyn BufRead>,
+    salida: &mut dyn Write,
+    formato: FormatoDeDiagnosticos,
+    configuracion: &Configuracion,
+    idioma: Idioma,
+) -> Result<(), Fallo> {
+    let entrada = leer_entrada_desde(entrada)?;
+    let formato_de_entrada = configuracion
+        .formato_de_entrada
+        .unwrap_or_else(|| FormatoDeEntrada::detectar(entrada.as_bytes()));
+    let diagnosticos = diagnosticar(
+        &entrada,
+        formato_de_entrada,
+        Some(&configuracion.limites),
+        opciones_de_parseo(configuracion),
+    );
+    let origen = configuracion.entrada.as_deref().unwrap_or("<stdin>");
+    for diagnostico in &diagnosticos {
+        match formato {
+            FormatoDeDiagnosticos::Texto => write!(
+                salida,
+                "{}",
+                diagnostico_a_texto(diagnostico, origen, idioma)
+            )?,
+            FormatoDeDiagnosticos::Json => {
+                writeln!(salida, "{}", diagnostico_a_json(diagnostico, idioma))?
+            }
+        }
+    }
+    salida.flush()?;
+    match diagnosticos.first() {
+        Some(primero) => Err(Fallo::Diagnosticos {
+            cantidad: diagnosticos.len(),
+            categoria: primero.categoria(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Dibuja la escena en un archivo SVG, con el separador decimal de la salida
+fn escribir_svg(
+    ruta: &str,
+    angulo: f64,
+    flatlanders: &[Flatlander],
+    configuracion: &Configuracion,
+) -> io::Result<()> {
+    let union = union_de_intervalos(sombras_desde_flatlanders(flatlanders, angulo));
+    let mut opciones = OpcionesDeSvg::default();
+    opciones.numeros.separador = configuracion.numeros.separador;
+    std::fs::write(ruta, escena_a_svg(flatlanders, angulo, &union, &opciones))
+}
 
-    let mut lineas = entrada.lines();
+/// Columnas del mapa de sombra cuando no se piden
+const COLUMNAS_DEL_MAPA: usize = 1000;
+/// Paso entre las filas del mapa cuando no se dan los angulos
+const PASO_DEL_MAPA: f64 = 0.1;
 
-    let (angulo, n) = match parsear_encabezado(lineas.next()) {
-        Some(val) => val,
-        None => return,
+/// Escribe el mapa de sombra angulo por posicion, en PPM si la ruta termina en .ppm y en PGM si no
+///
+/// Sin angulos pedidos se recorren los limites de angulo, dejando afuera los que no dan sombra finita
+fn escribir_mapa(
+    ruta: &str,
+    flatlanders: &[Flatlander],
+    configuracion: &Configuracion,
+) -> io::Result<()> {
+    let limites = &configuracion.limites;
+    let angulos = configuracion.mapa_angulos.clone().unwrap_or_else(|| {
+        angulos_en_rango(limites.angulo_min, limites.angulo_max, PASO_DEL_MAPA)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&angulo| angulo_de_sol_valido(angulo))
+            .collect()
+    });
+    let columnas = configuracion.mapa_columnas.unwrap_or(COLUMNAS_DEL_MAPA);
+    let mapa = MapaDeSombra::calcular(flatlanders, &angulos, columnas);
+    let modo = configuracion.mapa_modo;
+    let imagen = if ruta.to_ascii_lowercase().ends_with(".ppm") {
+        mapa_a_ppm(&mapa, modo)
+    } else {
+        mapa_a_pgm(&mapa, modo)
     };
+    std::fs::write(ruta, imagen)
+}
 
-    let flatlanders = match parsear_flatlanders(&mut lineas, n) {
-        Some(v) => v,
-        None => return,
+/// Angulos del barrido cuando no se piden: el sol sube de 10 a 80 grados de a uno
+const ANGULOS_DE_LA_ANIMACION: (f64, f64, f64) = (10.0, 80.0, 1.0);
+
+/// Escribe el barrido del sol como un SVG animado, como un SVG por angulo, o las dos cosas
+///
+/// El directorio de los cuadros se crea si no existe
+fn escribir_animacion(flatlanders: &[Flatlander], configuracion: &Configuracion) -> io::Result<()> {
+    let angulos = configuracion.animacion_angulos.clone().unwrap_or_else(|| {
+        let (desde, hasta, paso) = ANGULOS_DE_LA_ANIMACION;
+        angulos_en_rango(desde, hasta, paso).unwrap_or_default()
+    });
+    let mut opciones = OpcionesDeSvg::default();
+    opciones.numeros.separador = configuracion.numeros.separador;
+    if let Some(ruta) = &configuracion.animacion {
+        let segundos = configuracion
+            .animacion_segundos
+            .unwrap_or(SEGUNDOS_POR_CUADRO);
+        let svg = barrido_a_svg_animado(flatlanders, &angulos, segundos, &opciones);
+        std::fs::write(ruta, svg)?;
+    }
+    if let Some(directorio) = &configuracion.cuadros {
+        let directorio = std::path::Path::new(directorio);
+        std::fs::create_dir_all(directorio)?;
+        let cuadros = cuadros_del_barrido(flatlanders, &angulos, &opciones);
+        let cantidad = cuadros.len();
+        for (i, cuadro) in cuadros.into_iter().enumerate() {
+            std::fs::write(directorio.join(nombre_de_cuadro(i, cantidad)), cuadro)?;
+        }
+    }
+    Ok(())
+}
+
+/// Dibuja la escena como texto y, si se pidio, la curva del total entre los limites de angulo
+///
+/// El ancho sale del argumento, o de COLUMNS si la terminal lo exporta
+fn imprimir_dibujo(
+    salida: &mut dyn Write,
+    angulo: f64,
+    flatlanders: &[Flatlander],
+    juego: JuegoDeCaracteres,
+    configuracion: &Configuracion,
+) -> io::Result<()> {
+    let ancho = configuracion
+        .ancho
+        .or_else(|| {
+            std::env::var("COLUMNS")
+                .ok()
+                .and_then(|columnas| columnas.trim().parse().ok())
+                .filter(|&columnas| columnas > 0)
+        })
+        .unwrap_or(ANCHO_POR_DEFECTO);
+    let sombras = sombras_desde_flatlanders(flatlanders, angulo);
+    let perfil = perfil_de_profundidad(&sombras);
+    let union = union_de_intervalos(sombras);
+    let opciones = OpcionesDeTerminal {
+        ancho,
+        juego,
+        numeros: configuracion.numeros,
+        ..OpcionesDeTerminal::default()
     };
+    write!(
+        salida,
+        "{}",
+        escena_a_texto(flatlanders, angulo, &union, &perfil, &opciones)
+    )?;
+    if configuracion.curva {
+        // la curva necesita angulos con sombra finita
+        let limites = &configuracion.limites;
+        let desde = limites.angulo_min.max(1.0);
+        let hasta = limites.angulo_max.min(179.0);
+        let etiqueta_desde = desde.to_string();
+        let etiqueta_hasta = hasta.to_string();
+        let puntos = ancho.saturating_sub(etiqueta_desde.len() + etiqueta_hasta.len() + 2);
+        if let Some(curva) = curva_de_cobertura(flatlanders, desde, hasta, puntos, juego) {
+            writeln!(salida, "{etiqueta_desde} {curva} {etiqueta_hasta}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Escribe la tabla angulo y total para cada angulo del rango, ordenando los flatlanders una sola vez
+fn imprimir_tabla_de_angulos(
+    salida: &mut dyn Write,
+    flatlanders: Vec<Flatlander>,
+    angulos: &[f64],
+    numeros: &FormatoDeNumeros,
+) -> io::Result<()> {
+    let escena = EscenaOrdenada::nueva(flatlanders);
+    for (angulo, total) in escena.longitudes_por_angulo(angulos) {
+        writeln!(
+            salida,
+            "{} {}",
+            numeros.separador.localizar(angulo.to_string()),
+            numeros.formatear(total)
+        )?;
+    }
+    Ok(())
+}
+
+/// Tolerancia para buscar el angulo, la misma que pide la consigna para la salida
+const TOLERANCIA_OBJETIVO: f64 = 1e-4;
+
+/// Escribe los angulos donde la sombra total vale objetivo, buscando entre los limites de angulo
+///
+/// Como la busqueda necesita que la sombra baje con el angulo, el rango se corta en 90
+fn imprimir_angulos_para_objetivo(
+    salida: &mut dyn Write,
+    flatlanders: Vec<Flatlander>,
+    objetivo: f64,
+    configuracion: &Configuracion,
+    idioma: Idioma,
+) -> Result<(), Fallo> {
+    let escena = EscenaOrdenada::nueva(flatlanders);
+    let limites = &configuracion.limites;
+    let numeros = &configuracion.numeros;
+    let hasta = limites.angulo_max.min(90.0);
+    match angulo_para_longitud(
+        &escena,
+        objetivo,
+        limites.angulo_min,
+        hasta,
+        TOLERANCIA_OBJETIVO,
+    ) {
+        Some(ResultadoInverso::Angulos { desde, hasta }) => writeln!(
+            salida,
+            "{} {}",
+            numeros.formatear(desde),
+            numeros.formatear(hasta)
+        )?,
+        Some(ResultadoInverso::Inalcanzable { minima, maxima }) => writeln!(
+            salida,
+            "{} {} {}",
+            idioma.catalogo().inalcanzable,
+            numeros.formatear(minima),
+            numeros.formatear(maxima)
+        )?,
+        None => {
+            return Err(Fallo::Argumento(ErrorDeArgumento::ValorInvalido {
+                opcion: "--objetivo".to_string(),
+            }));
+        }
+    }
+    Ok(())
+}
 
-    let sombras = sombras_desde_flatlanders(&flatlanders, angulo);
-    let total = longitud_union(sombras);
-    println!("{:.13}", total);
+/// Escribe un intervalo por linea con la misma precision que el total
+fn imprimir_intervalos(
+    salida: &mut dyn Write,
+    titulo: &str,
+    intervalos: &[Intervalo],
+    numeros: &FormatoDeNumeros,
+) -> io::Result<()> {
+    writeln!(salida, "{titulo}")?;
+    for intervalo in intervalos {
+        writeln!(
+            salida,
+            "{} {}",
+            numeros.formatear(intervalo.inicio),
+            numeros.formatear(intervalo.fin)
+        )?;
+    }
+    Ok(())
+}
+
+/// Escribe el total de la escena y los reportes pedidos
+fn imprimir_escena(
+    salida: &mut dyn Write,
+    angulo: f64,
+    flatlanders: &[Flatlander],
+    configuracion: &Configuracion,
+    idioma: Idioma,
+) -> io::Result<()> {
+    let textos = idioma.catalogo();
+    let numeros = &configuracion.numeros;
+    let reportes = &configuracion.reportes;
+    let sombras = sombras_desde_flatlanders(flatlanders, angulo);
+
+    let union = union_de_intervalos(sombras.clone());
+    let total = longitud_total(&union);
+    writeln!(salida, "{}", numeros.formatear(total))?;
+
+    if reportes.intervalos {
+        imprimir_intervalos(salida, textos.sombra, &union, numeros)?;
+    }
+    if reportes.huecos
+        && let (Some(primera), Some(ultima)) = (union.first(), union.last())
+    {
+        let ventana = Intervalo::nuevo(primera.inicio, ultima.fin);
+        let huecos = huecos_iluminados(&union, &ventana);
+        imprimir_intervalos(salida, textos.luz, &huecos, numeros)?;
+    }
+    if reportes.profundidad {
+        let perfil = perfil_de_profundidad(&sombras);
+        writeln!(salida, "{}", textos.profundidad)?;
+        for (intervalo, profundidad) in &perfil {
+            writeln!(
+                salida,
+                "{} {} {profundidad}",
+                numeros.formatear(intervalo.inicio),
+                numeros.formatear(intervalo.fin)
+            )?;
+        }
+        if let Some((maxima, donde)) = profundidad_maxima(&perfil) {
+            imprimir_intervalos(
+                salida,
+                &format!("{} {maxima}", textos.maxima),
+                &donde,
+                numeros,
+            )?;
+        }
+    }
+    if reportes.atribucion {
+        writeln!(salida, "{}", textos.atribucion)?;
+        let atribuciones = atribuir_sombra(&sombras);
+        for (i, (flatlander, atribucion)) in flatlanders.iter().zip(&atribuciones).enumerate() {
+            writeln!(
+                salida,
+                "{} {} {} {} {}",
+                flatlander.etiqueta(i),
+                numeros.formatear(atribucion.propia),
+                numeros.formatear(atribucion.exclusiva),
+                numeros.formatear(atribucion.compartida),
+                numeros.formatear(atribucion.reparto)
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Centro de la ejecucion donde se hace la mezcla de todo
+///
+/// Lee la entrada, calcula lo que pidan los argumentos y lo escribe en la salida
+///
+/// El idioma recibido es el de la ayuda y los avisos. Lo que se escribe en la salida lo leen otros
+/// programas, asi que solo cambia de idioma con --idioma y no con el entorno
+fn correr(configuracion: &Configuracion, idioma: Idioma) -> Result<(), Fallo> {
+    if configuracion.ayuda {
+        print!("{}", idioma.catalogo().ayuda);
+        return Ok(());
+    }
+    let idioma_de_la_salida = configuracion.idioma.unwrap_or_default();
+    let entrada = abrir_entrada(configuracion.entrada.as_deref())?;
+    let mut salida = abrir_salida(configuracion.salida.as_deref())?;
+
+    if let Some(formato) = configuracion.lote {
+        ejecutar_lote(
+            entrada,
+            &mut salida,
+            formato,
+            configuracion,
+            idioma_de_la_salida,
+        )?;
+        return Ok(salida.flush()?);
+    }
+    if let Some(formato) = configuracion.diagnosticos {
+        return ejecutar_diagnosticos(
+            entrada,
+            &mut salida,
+            formato,
+            configuracion,
+            idioma_de_la_salida,
+        );
+    }
+
+    let (angulo, flatlanders) = ejecutar(entrada, configuracion, idioma)?;
+    if let Some(ruta) = &configuracion.svg {
+        escribir_svg(ruta, angulo, &flatlanders, configuracion)?;
+    }
+    if let Some(ruta) = &configuracion.mapa {
+        escribir_mapa(ruta, &flatlanders, configuracion)?;
+    }
+    if configuracion.animacion.is_some() || configuracion.cuadros.is_some() {
+        escribir_animacion(&flatlanders, configuracion)?;
+    }
+    let limites = &configuracion.limites;
+    if let Some(angulos) = &configuracion.angulos {
+        imprimir_tabla_de_angulos(&mut salida, flatlanders, angulos, &configuracion.numeros)?;
+    } else if let Some(juego) = configuracion.dibujo {
+        imprimir_dibujo(&mut salida, angulo, &flatlanders, juego, configuracion)?;
+    } else if let Some(objetivo) = configuracion.objetivo {
+        imprimir_angulos_para_objetivo(
+            &mut salida,
+            flatlanders,
+            objetivo,
+            configuracion,
+            idioma_de_la_salida,
+        )?;
+    } else {
+        match configuracion.formato_de_salida {
+            FormatoDeSalida::Texto => {
+                imprimir_escena(
+                    &mut salida,
+                    angulo,
+                    &flatlanders,
+                    configuracion,
+                    idioma_de_la_salida,
+                )?;
+            }
+            FormatoDeSalida::Csv => {
+                let resumen = ResumenDeEscena::calcular(angulo, &flatlanders, limites);
+                write!(
+                    salida,
+                    "{}",
+                    resumen_a_csv(&resumen, &configuracion.numeros)
+                )?;
+            }
+            FormatoDeSalida::Json => {
+                let resumen = ResumenDeEscena::calcular(angulo, &flatlanders, limites);
+                writeln!(salida, "{}", resumen_a_json(&resumen))?;
+            }
+        }
+    }
+    Ok(salida.flush()?)
+}
+
+/// Punto de entrada: los errores van a la salida de errores y cada categoria tiene su codigo
+///
+/// Las opciones estan explicadas en el texto de --help. El idioma sale de --idioma, y si no se da
+/// o los argumentos no sirven, de LC_ALL, LC_MESSAGES o LANG
+fn main() -> ExitCode {
+    let argumentos: Vec<String> = std::env::args().skip(1).collect();
+    let configuracion = parsear_argumentos(&argumentos);
+    let idioma = configuracion
+        .as_ref()
+        .ok()
+        .and_then(|configuracion| configuracion.idioma)
+        .or_else(|| idioma_del_entorno(|variable| std::env::var(variable).ok()))
+        .unwrap_or_default();
+    let resultado = configuracion
+        .map_err(Fallo::from)
+        .and_then(|configuracion| correr(&configuracion, idioma));
+    match resultado {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(fallo) => {
+            eprintln!("{}: {}", idioma.catalogo().error, fallo.mensaje(idioma));
+            ExitCode::from(fallo.codigo())
+        }
+    }
 }
@@ -0,0 +1,284 @@
+/// Archivo para armar el mapa de sombra angulo por posicion y escribirlo como imagen PGM o PPM
+use crate::barrido::rango_de_sombras;
+use crate::flatlander::Flatlander;
+use crate::geometria::{desplazamiento_con_tangente, tangente};
+#[cfg(test)]
+use crate::pruebas::dos_flatlanders;
+use std::fmt::Write;
+
+/// Valor maximo de cada canal en las imagenes
+const VALOR_MAXIMO: u32 = 255;
+/// Cantidad de numeros por linea, los formatos planos piden lineas de no mas de 70 caracteres
+const NUMEROS_POR_LINEA: usize = 16;
+
+/// Que muestra cada pixel del mapa
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ModoDeMapa {
+    /// Solo si la posicion esta a la sombra o no
+    #[default]
+    Sombra,
+    /// Cuantas sombras cubren la posicion, relativo a la mayor del mapa
+    Profundidad,
+}
+
+/// Profundidad de sombra para cada angulo (filas) y cada posicion del piso (columnas)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapaDeSombra {
+    /// Angulo de cada fila, la primera fila es la de arriba de la imagen
+    pub angulos: Vec<f64>,
+    /// Borde izquierdo de la primera columna
+    pub desde: f64,
+    /// Borde derecho de la ultima columna
+    pub hasta: f64,
+    /// Cantidad de columnas
+    pub columnas: usize,
+    /// Profundidad de cada pixel, fila por fila
+    pub profundidades: Vec<u32>,
+}
+
+impl MapaDeSombra {
+    /// Arma el mapa eligiendo el rango de posiciones para que entren todas las sombras
+    pub fn calcular(flatlanders: &[Flatlander], angulos: &[f64], columnas: usize) -> Self {
+        let (desde, hasta) = rango_de_sombras(flatlanders, angulos);
+        Self::con_rango(flatlanders, angulos, desde, hasta, columnas)
+    }
+
+    /// Arma el mapa para las posiciones de desde a hasta
+    ///
+    /// Cada fila es una pasada por los flatlanders con la tangente de ese angulo, O(n + columnas),
+    /// asi que un mapa de 1000 por 1000 no necesita ordenar nada
+    pub fn con_rango(
+        flatlanders: &[Flatlander],
+        angulos: &[f64],
+        desde: f64,
+        hasta: f64,
+        columnas: usize,
+    ) -> Self {
+        let columnas = columnas.max(1);
+        let ancho_de_columna = (hasta - desde) / columnas as f64;
+        let mut profundidades = Vec::with_capacity(angulos.len() * columnas);
+        for &angulo in angulos {
+            profundidades.extend(profundidad_en_columnas(
+                flatlanders,
+                angulo,
+                desde,
+                ancho_de_columna,
+                columnas,
+            ));
+        }
+        Self {
+            angulos: angulos.to_vec(),
+            desde,
+            hasta,
+            columnas,
+            profundidades,
+        }
+    }
+
+    /// Cantidad de filas, una por angulo
+    pub fn filas(&self) -> usize {
+        self.angulos.len()
+    }
+
+    /// Profundidad en una fila y una columna
+    pub fn profundidad(&self, fila: usize, columna: usize) -> u32 {
+        self.profundidades[fila * self.columnas + columna]
+    }
+
+    /// Mayor profundidad de todo el mapa, 0 si no hay sombra
+    pub fn profundidad_maxima(&self) -> u32 {
+        self.profundidades.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Funcion interna que lleva la profundidad a un valor entre 0 (luz) y 1 (lo mas oscuro)
+    fn intensidades(&self, modo: ModoDeMapa) -> impl Iterator<Item = f64> + '_ {
+        let maxima = match modo {
+            ModoDeMapa::Sombra => 1,
+            ModoDeMapa::Profundidad => self.profundidad_maxima().max(1),
+        };
+        self.profundidades
+            .iter()
+            .map(move |&p| f64::from(p.min(maxima)) / f64::from(maxima))
+    }
+}
+
+/// Cuenta cuantas sombras cubren el centro de cada columna para un angulo, en O(n + columnas)
+///
+/// La columna i va de desde + i * ancho_de_columna a desde + (i + 1) * ancho_de_columna. Cada
+/// sombra suma 1 en la columna de su primer centro y resta 1 despues del ultimo, y al final se
+/// acumula. Una sombra que no llega a cubrir ningun centro no aparece
+///
+/// Es la idea de EscenaOrdenada, que cada angulo cueste una sola pasada lineal, pero sin ordenar:
+/// las diferencias se acumulan en el orden de las columnas y no en el de las sombras, asi que los
+/// flatlanders pueden venir en cualquier orden y ordenarlos una vez no ahorraria nada por fila
+pub fn profundidad_en_columnas(
+    flatlanders: &[Flatlander],
+    angulo: f64,
+    desde: f64,
+    ancho_de_columna: f64,
+    columnas: usize,
+) -> Vec<u32> {
+    let mut diferencias = vec![0_i64; columnas + 1];
+    let tangente = tangente(angulo);
+    let columnas_por_unidad = 1.0 / ancho_de_columna;
+    for flatlander in flatlanders {
+        let punta = flatlander.x + desplazamiento_con_tangente(flatlander.h, angulo, tangente);
+        let (inicio, fin) = if punta < flatlander.x {
+            (punta, flatlander.x)
+        } else {
+            (flatlander.x, punta)
+        };
+        // centros c_i = desde + (i + 0.5) * ancho con inicio <= c_i <= fin
+        let primera = ((inicio - desde) * columnas_por_unidad - 0.5)
+            .ceil()
+            .max(0.0);
+        let ultima = ((fin - desde) * columnas_por_unidad - 0.5).floor();
+        if ultima < primera || primera >= columnas as f64 {
+            continue;
+        }
+        let ultima = (ultima as usize).min(columnas - 1);
+        diferencias[primera as usize] += 1;
+        diferencias[ultima + 1] -= 1;
+    }
+    let mut profundidad = 0;
+    diferencias[..columnas]
+        .iter()
+        .map(|diferencia| {
+            profundidad += diferencia;
+            profundidad as u32
+        })
+        .collect()
+}
+
+/// Escribe el mapa como PGM plano (P2): la luz es blanca y la sombra es mas oscura cuanto mas profunda
+pub fn mapa_a_pgm(mapa: &MapaDeSombra, modo: ModoDeMapa) -> String {
+    let grises = mapa
+        .intensidades(modo)
+        .map(|intensidad| [VALOR_MAXIMO - (intensidad * f64::from(VALOR_MAXIMO)).round() as u32]);
+    imagen_plana("P2", mapa, grises)
+}
+
+/// Escribe el mapa como PPM plano (P3): la luz es celeste y la sombra va de amarillo a rojo oscuro
+pub fn mapa_a_ppm(mapa: &MapaDeSombra, modo: ModoDeMapa) -> String {
+    let colores = mapa.intensidades(modo).map(color_de_sombra);
+    imagen_plana("P3", mapa, colores)
+}
+
+/// Funcion interna que elige el color de un pixel
+fn color_de_sombra(intensidad: f64) -> [u32; 3] {
+    const LUZ: [f64; 3] = [200.0, 230.0, 255.0];
+    const POCA: [f64; 3] = [255.0, 210.0, 80.0];
+    const MUCHA: [f64; 3] = [120.0, 20.0, 10.0];
+    if intensidad <= 0.0 {
+        return LUZ.map(|canal| canal as u32);
+    }
+    let mut color = [0; 3];
+    for (i, canal) in color.iter_mut().enumerate() {
+        *canal = (POCA[i] + (MUCHA[i] - POCA[i]) * intensidad).round() as u32;
+    }
+    color
+}
+
+/// Funcion interna que arma el encabezado y los pixeles en lineas cortas
+///
+/// El comentario del encabezado deja anotados los angulos y las posiciones del mapa
+fn imagen_plana<const CANALES: usize>(
+    magico: &str,
+    mapa: &MapaDeSombra,
+    pixeles: impl Iterator<Item = [u32; CANALES]>,
+) -> String {
+    let mut imagen = String::new();
+    let _ = writeln!(imagen, "{magico}");
+    if let (Some(primero), Some(ultimo)) = (mapa.angulos.first(), mapa.angulos.last()) {
+        let _ = writeln!(
+            imagen,
+            "# angulos {primero} a {ultimo}, posiciones {} a {}",
+            mapa.desde, mapa.hasta
+        );
+    }
+    let _ = writeln!(imagen, "{} {}", mapa.columnas, mapa.filas());
+    let _ = writeln!(imagen, "{VALOR_MAXIMO}");
+    let mut en_la_linea = 0;
+    for pixel in pixeles {
+        for valor in pixel {
+            if en_la_linea > 0 {
+                imagen.push(' ');
+            }
+            let _ = write!(imagen, "{valor}");
+            en_la_linea += 1;
+            if en_la_linea == NUMEROS_POR_LINEA {
+                imagen.push('\n');
+                en_la_linea = 0;
+            }
+        }
+    }
+    if en_la_linea > 0 {
+        imagen.push('\n');
+    }
+    imagen
+}
+
+#[test]
+fn profundidad_en_los_centros_de_las_columnas() {
+    // desordenados a proposito, la cuenta no depende del orden
+    let flatlanders = vec![
+        Flatlander::nuevo(8.0, 1.0),
+        Flatlander::nuevo(0.0, 4.0),
+        Flatlander::nuevo(2.0, 1.0),
+    ];
+    // a 45 las sombras son [0, 4], [2, 3] y [8, 9], con columnas de 1 desde 0 los centros son 0.5, 1.5, ...
+    assert_eq!(
+        profundidad_en_columnas(&flatlanders, 45.0, 0.0, 1.0, 10),
+        vec![1, 1, 2, 1, 0, 0, 0, 0, 1, 0]
+    );
+    // pasando 90 la de 8 va a [7, 8] y las columnas que quedan afuera no se cuentan
+    assert_eq!(
+        profundidad_en_columnas(&flatlanders, 135.0, 5.0, 1.0, 3),
+        vec![0, 0, 1]
+    );
+}
+
+#[test]
+fn la_sombra_se_acorta_con_el_angulo() {
+    let mapa = MapaDeSombra::con_rango(&dos_flatlanders(1.0), &[45.0, 90.0], 0.0, 4.0, 4);
+    assert_eq!(mapa.filas(), 2);
+    // a 45 las sombras son [0, 2] y [1, 3], a 90 no hay sombra
+    assert_eq!(mapa.profundidades, vec![1, 2, 1, 0, 0, 0, 0, 0]);
+    assert_eq!(mapa.profundidad(0, 1), 2);
+    assert_eq!(mapa.profundidad_maxima(), 2);
+}
+
+#[test]
+fn el_rango_cubre_todas_las_sombras() {
+    let mapa = MapaDeSombra::calcular(&dos_flatlanders(1.0), &[45.0, 60.0], 3);
+    assert_eq!(mapa.desde, 0.0);
+    assert!((mapa.hasta - 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn pgm_plano() {
+    let mapa = MapaDeSombra::con_rango(&dos_flatlanders(1.0), &[45.0], 0.0, 4.0, 4);
+    assert_eq!(
+        mapa_a_pgm(&mapa, ModoDeMapa::Sombra),
+        "P2\n# angulos 45 a 45, posiciones 0 a 4\n4 1\n255\n0 0 0 255\n"
+    );
+    assert_eq!(
+        mapa_a_pgm(&mapa, ModoDeMapa::Profundidad),
+        "P2\n# angulos 45 a 45, posiciones 0 a 4\n4 1\n255\n127 0 127 255\n"
+    );
+}
+
+#[test]
+fn ppm_plano_con_lineas_cortas() {
+    let angulos: Vec<f64> = (0..10).map(|i| 20.0 + f64::from(i)).collect();
+    let mapa = MapaDeSombra::calcular(&dos_flatlanders(1.0), &angulos, 30);
+    let ppm = mapa_a_ppm(&mapa, ModoDeMapa::Profundidad);
+    let mut lineas = ppm.lines();
+    assert_eq!(lineas.next(), Some("P3"));
+    assert!(lineas.next().unwrap().starts_with('#'));
+    assert_eq!(lineas.next(), Some("30 10"));
+    assert_eq!(lineas.next(), Some("255"));
+    let valores: Vec<&str> = lineas.clone().flat_map(str::split_whitespace).collect();
+    assert_eq!(valores.len(), 30 * 10 * 3);
+    assert!(lineas.all(|linea| linea.len() <= 70));
+}
@@ -0,0 +1,350 @@
+/// Archivo con los textos que ve el usuario, en espanol y en ingles
+use crate::argumentos::{AYUDA, AYUDA_EN_INGLES, ErrorDeArgumento};
+use crate::parseador::{Advertencia, ErrorParseo};
+use std::str::FromStr;
+
+/// Idioma de los mensajes
+///
+/// En espanol los errores se muestran exactamente como siempre, asi que es el que va por defecto
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Idioma {
+    /// Los mensajes de siempre
+    #[default]
+    Espanol,
+    /// Los mensajes en ingles, con el codigo del error adelante
+    Ingles,
+}
+
+impl FromStr for Idioma {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "es" => Ok(Idioma::Espanol),
+            "en" => Ok(Idioma::Ingles),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Idioma {
+    /// Saca el idioma del valor de una variable como LANG, por ejemplo en_US.UTF-8
+    ///
+    /// Devuelve None si el idioma no es uno de los que hay, como pasa con C o POSIX
+    pub fn desde_lang(lang: &str) -> Option<Self> {
+        let codigo = lang.split(['_', '.', '@', '-']).next()?;
+        codigo.to_ascii_lowercase().parse().ok()
+    }
+
+    /// Textos fijos en este idioma
+    pub fn catalogo(self) -> &'static Catalogo {
+        match self {
+            Idioma::Espanol => &ESPANOL,
+            Idioma::Ingles => &INGLES,
+        }
+    }
+}
+
+/// Variables del entorno que eligen el idioma, de la que mas manda a la que menos
+const VARIABLES_DE_IDIOMA: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Saca el idioma del entorno con la precedencia de siempre: manda la primera de LC_ALL,
+/// LC_MESSAGES y LANG que tenga valor
+///
+/// Recibe como leer cada variable para poder probarlo sin tocar el entorno. Devuelve None si la
+/// que manda no es un idioma que haya, aunque las que le siguen lo sean
+pub fn idioma_del_entorno(leer: impl Fn(&str) -> Option<String>) -> Option<Idioma> {
+    let valor = VARIABLES_DE_IDIOMA
+        .iter()
+        .filter_map(|variable| leer(variable))
+        .find(|valor| !valor.is_empty())?;
+    Idioma::desde_lang(&valor)
+}
+
+/// Textos fijos que imprime el binario: los prefijos de los avisos y los titulos de los reportes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Catalogo {
+    /// Prefijo de los errores
+    pub error: &'static str,
+    /// Prefijo de las advertencias
+    pub advertencia: &'static str,
+    /// Titulo de las regiones a la sombra
+    pub sombra: &'static str,
+    /// Titulo de las regiones iluminadas
+    pub luz: &'static str,
+    /// Titulo del perfil de cobertura
+    pub profundidad: &'static str,
+    /// Titulo de donde se da la profundidad maxima
+    pub maxima: &'static str,
+    /// Titulo del reparto de la sombra
+    pub atribucion: &'static str,
+    /// Cuando ningun angulo da la longitud pedida
+    pub inalcanzable: &'static str,
+    /// Cuando no se puede escribir la salida
+    pub salida_fallida: &'static str,
+    /// Texto de --help
+    pub ayuda: &'static str,
+}
+
+/// Los textos de siempre
+pub const ESPANOL: Catalogo = Catalogo {
+    error: "Error",
+    advertencia: "Advertencia",
+    sombra: "sombra",
+    luz: "luz",
+    profundidad: "profundidad",
+    maxima: "maxima",
+    atribucion: "atribucion",
+    inalcanzable: "inalcanzable",
+    salida_fallida: "no se pudo escribir la salida",
+    ayuda: AYUDA,
+};
+
+/// Los textos en ingles
+pub const INGLES: Catalogo = Catalogo {
+    error: "Error",
+    advertencia: "Warning",
+    sombra: "shadow",
+    luz: "light",
+    profundidad: "depth",
+    maxima: "maximum",
+    atribucion: "attribution",
+    inalcanzable: "unreachable",
+    salida_fallida: "could not write the output",
+    ayuda: AYUDA_EN_INGLES,
+};
+
+/// Mensaje del error sin comillas ni posicion
+pub fn mensaje_de_error(error: &ErrorParseo, idioma: Idioma) -> &'static str {
+    let (espanol, ingles) = match error {
+        ErrorParseo::Io { .. } => ("IO", "Input could not be read"),
+        ErrorParseo::LineaFaltante { .. } => ("Linea faltante", "Missing line"),
+        ErrorParseo::ValorFaltante { .. } => ("Valor faltante", "Missing value"),
+        ErrorParseo::NumeroInvalido { .. } => ("Numero invalido", "Invalid number"),
+        ErrorParseo::FueraDeRango { .. } => ("Fuera de rango", "Out of range"),
+        ErrorParseo::DatosSobrantes { .. } => ("Datos sobrantes", "Unexpected extra data"),
+        ErrorParseo::DirectivaInvalida { .. } => ("Directiva invalida", "Invalid directive"),
+        ErrorParseo::DirectivaFaltante { .. } => ("Directiva faltante", "Missing directive"),
+        ErrorParseo::FormatoInvalido { .. } => ("Formato invalido", "Invalid format"),
+        ErrorParseo::AnguloDegenerado { .. } => ("Angulo degenerado", "Degenerate sun angle"),
+    };
+    match idioma {
+        Idioma::Espanol => espanol,
+        Idioma::Ingles => ingles,
+    }
+}
+
+/// Error de parseo tal como lo muestra el binario
+///
+/// En espanol es el mensaje de siempre entre comillas, para no romper a nadie. En ingles va el
+/// codigo estable y despues el mensaje
+pub fn error_localizado(error: &ErrorParseo, idioma: Idioma) -> String {
+    match idioma {
+        Idioma::Espanol => error.to_string(),
+        Idioma::Ingles => format!("{} {}", error.codigo(), mensaje_de_error(error, idioma)),
+    }
+}
+
+/// Error de los argumentos en el idioma pedido
+pub fn error_de_argumento_localizado(error: &ErrorDeArgumento, idioma: Idioma) -> String {
+    match (idioma, error) {
+        (Idioma::Espanol, _) => error.to_string(),
+        (Idioma::Ingles, ErrorDeArgumento::ValorInvalido { opcion }) => {
+            format!("invalid value for {opcion}")
+        }
+        (Idioma::Ingles, ErrorDeArgumento::OpcionDesconocida { opcion }) => {
+            format!("unknown option {opcion}")
+        }
+        (Idioma::Ingles, ErrorDeArgumento::EntradaRepetida { entrada }) => {
+            format!("extra input {entrada}, only one is read")
+        }
+        (Idioma::Ingles, ErrorDeArgumento::OpcionesIncompatibles { opcion, con }) => {
+            format!("{opcion} cannot be used with {con}")
+        }
+    }
+}
+
+/// Advertencia del modo permisivo en el idioma pedido
+pub fn advertencia_localizada(advertencia: &Advertencia, idioma: Idioma) -> String {
+    match (idioma, advertencia) {
+        (Idioma::Espanol, _) => advertencia.to_string(),
+        (
+            Idioma::Ingles,
+            Advertencia::CantidadDistinta {
+                declarada,
+                encontrada,
+            },
+        ) => format!("the header says {declarada} flatlanders but there are {encontrada}"),
+    }
+}
+
+/// Resumen de los diagnosticos cuando la entrada tiene errores
+pub fn errores_en_la_entrada(cantidad: usize, idioma: Idioma) -> String {
+    match idioma {
+        Idioma::Espanol if cantidad == 1 => "la entrada tiene 1 error".to_string(),
+        Idioma::Espanol => format!("la entrada tiene {cantidad} errores"),
+        Idioma::Ingles if cantidad == 1 => "the input has 1 error".to_string(),
+        Idioma::Ingles => format!("the input has {cantidad} errors"),
+    }
+}
+
+#[test]
+fn idioma_desde_lang() {
+    assert_eq!(Idioma::desde_lang("en_US.UTF-8"), Some(Idioma::Ingles));
+    assert_eq!(Idioma::desde_lang("es_AR.UTF-8"), Some(Idioma::Espanol));
+    assert_eq!(Idioma::desde_lang("EN"), Some(Idioma::Ingles));
+    assert_eq!(Idioma::desde_lang("C"), None);
+    assert_eq!(Idioma::desde_lang(""), None);
+}
+
+#[test]
+fn idioma_del_entorno_respeta_la_precedencia() {
+    let entorno = |variables: &'static [(&'static str, &'static str)]| {
+        move |nombre: &str| {
+            variables
+                .iter()
+                .find(|(variable, _)| *variable == nombre)
+                .map(|(_, valor)| valor.to_string())
+        }
+    };
+    assert_eq!(
+        idioma_del_entorno(entorno(&[("LANG", "en_US.UTF-8")])),
+        Some(Idioma::Ingles)
+    );
+    assert_eq!(
+        idioma_del_entorno(entorno(&[
+            ("LC_MESSAGES", "es_AR.UTF-8"),
+            ("LANG", "en_US.UTF-8")
+        ])),
+        Some(Idioma::Espanol)
+    );
+    assert_eq!(
+        idioma_del_entorno(entorno(&[("LC_ALL", "C"), ("LC_MESSAGES", "en_US.UTF-8")])),
+        None
+    );
+    assert_eq!(
+        idioma_del_entorno(entorno(&[("LC_ALL", ""), ("LANG", "en")])),
+        Some(Idioma::Ingles)
+    );
+    assert_eq!(idioma_del_entorno(entorno(&[])), None);
+}
+
+#[test]
+fn en_espanol_el_error_es_el_de_siempre() {
+    let error = ErrorParseo::NumeroInvalido {
+        linea: 2,
+        columna: 1,
+        token: "x".to_string(),
+    };
+    assert_eq!(
+        error_localizado(&error, Idioma::Espanol),
+        "\"Numero invalido\""
+    );
+    assert_eq!(
+        error_localizado(&error, Idioma::Ingles),
+        "E004 Invalid number"
+    );
+    assert_eq!(
+        format!("\"{}\"", mensaje_de_error(&error, Idioma::Espanol)),
+        error.to_string()
+    );
+}
+
+#[test]
+fn los_mensajes_en_espanol_coinciden_con_los_de_siempre() {
+    let token = String::new;
+    let errores = [
+        (ErrorParseo::Io { mensaje: token() }, "\"IO\""),
+        (
+            ErrorParseo::LineaFaltante { linea: 1 },
+            "\"Linea faltante\"",
+        ),
+        (
+            ErrorParseo::ValorFaltante {
+                linea: 1,
+                columna: 1,
+            },
+            "\"Valor faltante\"",
+        ),
+        (
+            ErrorParseo::NumeroInvalido {
+                linea: 1,
+                columna: 1,
+                token: token(),
+            },
+            "\"Numero invalido\"",
+        ),
+        (
+            ErrorParseo::FueraDeRango {
+                linea: 1,
+                columna: 1,
+                token: token(),
+                minimo: 0.0,
+                maximo: 1.0,
+            },
+            "\"Fuera de rango\"",
+        ),
+        (
+            ErrorParseo::DatosSobrantes {
+                linea: 1,
+                columna: 1,
+                token: token(),
+            },
+            "\"Datos sobrantes\"",
+        ),
+        (
+            ErrorParseo::DirectivaInvalida {
+                linea: 1,
+                columna: 1,
+                token: token(),
+            },
+            "\"Directiva invalida\"",
+        ),
+        (
+            ErrorParseo::DirectivaFaltante {
+                linea: 1,
+                clave: token(),
+            },
+            "\"Directiva faltante\"",
+        ),
+        (
+            ErrorParseo::FormatoInvalido {
+                linea: 1,
+                columna: 1,
+                token: token(),
+            },
+            "\"Formato invalido\"",
+        ),
+        (
+            ErrorParseo::AnguloDegenerado {
+                linea: 1,
+                columna: 1,
+                token: token(),
+            },
+            "\"Angulo degenerado\"",
+        ),
+    ];
+    for (error, esperado) in &errores {
+        assert_eq!(error.to_string(), *esperado);
+        assert_eq!(error_localizado(error, Idioma::Espanol), *esperado);
+    }
+    let codigos: std::collections::HashSet<_> =
+        errores.iter().map(|(error, _)| error.codigo()).collect();
+    assert_eq!(codigos.len(), errores.len());
+}
+
+#[test]
+fn un_solo_error_va_en_singular() {
+    assert_eq!(
+        errores_en_la_entrada(1, Idioma::Espanol),
+        "la entrada tiene 1 error"
+    );
+    assert_eq!(
+        errores_en_la_entrada(1, Idioma::Ingles),
+        "the input has 1 error"
+    );
+    assert_eq!(
+        errores_en_la_entrada(3, Idioma::Ingles),
+        "the input has 3 errors"
+    );
+}
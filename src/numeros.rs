@@ -0,0 +1,269 @@
+/// Archivo con un parseo de numeros rapido que mira directo los bytes y el separador decimal
+use std::borrow::Cow;
+use std::num::ParseFloatError;
+
+/// Mayor mantisa que entra exacta en un f64
+const MANTISA_MAX: u64 = 1 << 53;
+
+/// Potencias de 10 que se representan exactas en un f64
+const POTENCIAS_DE_10: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+/// Parsea un f64 dando exactamente el mismo resultado que str::parse
+///
+/// Los enteros y decimales simples ([+-]digitos[.digitos]) se arman directo desde los bytes:
+/// si la mantisa entra en 53 bits y hay a lo sumo 22 decimales, mantisa / 10^decimales es una
+/// sola division IEEE entre dos numeros exactos y da el mismo redondeo que str::parse
+///
+/// Todo lo demas (exponentes, inf, NaN, muchos digitos) va a str::parse
+pub fn parsear_f64_rapido(texto: &str) -> Result<f64, ParseFloatError> {
+    match parsear_decimal_simple(texto.as_bytes()) {
+        Some(valor) => Ok(valor),
+        None => texto.parse::<f64>(),
+    }
+}
+
+/// Funcion interna del camino rapido, devuelve None si el texto no es un decimal simple
+fn parsear_decimal_simple(bytes: &[u8]) -> Option<f64> {
+    let (negativo, resto) = match bytes.split_first() {
+        Some((b'-', resto)) => (true, resto),
+        Some((b'+', resto)) => (false, resto),
+        _ => (false, bytes),
+    };
+
+    let mut mantisa: u64 = 0;
+    let mut digitos = 0usize;
+    let mut decimales = 0usize;
+    let mut vio_punto = false;
+
+    for &byte in resto {
+        match byte {
+            b'0'..=b'9' => {
+                mantisa = mantisa
+                    .checked_mul(10)?
+                    .checked_add(u64::from(byte - b'0'))?;
+                if mantisa > MANTISA_MAX {
+                    return None;
+                }
+                digitos += 1;
+                if vio_punto {
+                    decimales += 1;
+                }
+            }
+            b'.' if !vio_punto => vio_punto = true,
+            _ => return None,
+        }
+    }
+    if digitos == 0 || decimales >= POTENCIAS_DE_10.len() {
+        return None;
+    }
+
+    let valor = mantisa as f64 / POTENCIAS_DE_10[decimales];
+    Some(if negativo { -valor } else { valor })
+}
+
+/// Con que caracter se separan los decimales, en la entrada y en la salida
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeparadorDecimal {
+    /// 12.5, como siempre
+    #[default]
+    Punto,
+    /// 12,5, como exportan las planillas en castellano, y el punto separa los miles
+    Coma,
+}
+
+impl SeparadorDecimal {
+    /// Pasa el texto de un numero a la forma que entiende str::parse, o None si no es valido
+    ///
+    /// Con punto se devuelve el mismo texto. Con coma las reglas para que no haya dudas son:
+    ///
+    /// - Puede haber a lo sumo una coma y es la de los decimales: 12,5 es 12.5
+    /// - Un punto es siempre de miles, asi que los grupos despues del primero tienen que tener
+    ///   tres digitos: 1.234 es 1234, 1.234,5 es 1234.5 y 12.5 o 1.23 no son validos
+    /// - Puede haber un exponente al final, 1,5e3 es 1500, y signo al principio
+    pub fn normalizar<'a>(&self, texto: &'a str) -> Option<Cow<'a, str>> {
+        if *self == SeparadorDecimal::Punto {
+            return Some(Cow::Borrowed(texto));
+        }
+        let (mantisa, exponente) = match texto.find(['e', 'E']) {
+            Some(i) => texto.split_at(i),
+            None => (texto, ""),
+        };
+        let (signo, mantisa) = match mantisa.strip_prefix(['+', '-']) {
+            Some(resto) => (&mantisa[..1], resto),
+            None => ("", mantisa),
+        };
+        let (entera, fraccion) = match mantisa.split_once(',') {
+            Some((entera, fraccion)) => (entera, Some(fraccion)),
+            None => (mantisa, None),
+        };
+
+        let solo_digitos = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        let mut grupos = entera.split('.');
+        let primero = grupos.next().unwrap_or("");
+        let hay_miles = entera.contains('.');
+        if !solo_digitos(primero) || (hay_miles && !(1..=3).contains(&primero.len())) {
+            return None;
+        }
+        if !grupos.all(|g| g.len() == 3 && solo_digitos(g)) {
+            return None;
+        }
+        if let Some(fraccion) = fraccion
+            && (fraccion.is_empty() || !solo_digitos(fraccion))
+        {
+            return None;
+        }
+        if entera.is_empty() && fraccion.is_none() {
+            return None;
+        }
+
+        let mut normalizado = String::with_capacity(texto.len());
+        normalizado.push_str(signo);
+        normalizado.extend(entera.chars().filter(|&c| c != '.'));
+        if let Some(fraccion) = fraccion {
+            normalizado.push('.');
+            normalizado.push_str(fraccion);
+        }
+        normalizado.push_str(exponente);
+        Some(Cow::Owned(normalizado))
+    }
+
+    /// Cambia el punto decimal de un numero ya formateado por el separador elegido
+    pub fn localizar(&self, texto: String) -> String {
+        match self {
+            SeparadorDecimal::Punto => texto,
+            SeparadorDecimal::Coma => texto.replace('.', ","),
+        }
+    }
+}
+
+/// Decimales con los que se escriben los resultados si no se pide otra cosa, los de la consigna
+pub const DECIMALES_POR_DEFECTO: usize = 13;
+
+/// Como se escriben los numeros de la salida: cuantos decimales y con que separador
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormatoDeNumeros {
+    /// Cantidad fija de decimales
+    pub decimales: usize,
+    /// Separador decimal
+    pub separador: SeparadorDecimal,
+}
+
+impl Default for FormatoDeNumeros {
+    fn default() -> Self {
+        Self {
+            decimales: DECIMALES_POR_DEFECTO,
+            separador: SeparadorDecimal::Punto,
+        }
+    }
+}
+
+impl FormatoDeNumeros {
+    /// Escribe el numero con los decimales y el separador elegidos
+    pub fn formatear(&self, valor: f64) -> String {
+        self.separador
+            .localizar(format!("{valor:.*}", self.decimales))
+    }
+}
+
+#[test]
+fn enteros_y_decimales_simples() {
+    assert_eq!(parsear_f64_rapido("0"), Ok(0.0));
+    assert_eq!(parsear_f64_rapido("300000"), Ok(300_000.0));
+    assert_eq!(parsear_f64_rapido("12.5"), Ok(12.5));
+    assert_eq!(parsear_f64_rapido(".5"), Ok(0.5));
+    assert_eq!(parsear_f64_rapido("5."), Ok(5.0));
+    assert_eq!(parsear_f64_rapido("+7"), Ok(7.0));
+    assert!(parsear_f64_rapido("-0").unwrap().is_sign_negative());
+}
+
+#[test]
+fn formas_raras_van_al_parseo_estandar() {
+    assert_eq!(parsear_f64_rapido("1e3"), Ok(1000.0));
+    assert_eq!(parsear_f64_rapido("inf"), Ok(f64::INFINITY));
+    assert!(parsear_f64_rapido("NaN").unwrap().is_nan());
+    assert!(parsear_f64_rapido("").is_err());
+    assert!(parsear_f64_rapido(".").is_err());
+    assert!(parsear_f64_rapido("1.2.3").is_err());
+    assert!(parsear_f64_rapido("abc").is_err());
+    assert!(parsear_f64_rapido("--1").is_err());
+}
+
+#[test]
+fn da_los_mismos_bits_que_str_parse() {
+    let mut generador = crate::pruebas::Generador::nuevo(12345);
+    let mut textos: Vec<String> = vec![
+        "0.1".into(),
+        "0.3".into(),
+        "9007199254740993".into(),
+        "9007199254740992".into(),
+        "123456789012345678901234567890".into(),
+        "0.0000000000000000000001".into(),
+        "0.00000000000000000000001".into(),
+        "33.7".into(),
+        "999.9999999999999".into(),
+        "-0.0".into(),
+    ];
+    for _ in 0..20_000 {
+        let semilla = generador.siguiente();
+        let entero = (semilla >> 40) % 1_000_000;
+        let fraccion = (semilla >> 8) % 100_000_000;
+        let decimales = ((semilla >> 4) % 9) as usize;
+        let fraccion = format!("{fraccion:08}");
+        textos.push(format!("{entero}.{}", &fraccion[..decimales]));
+    }
+    for texto in textos {
+        let esperado = texto.parse::<f64>().map(f64::to_bits);
+        let obtenido = parsear_f64_rapido(&texto).map(f64::to_bits);
+        assert_eq!(obtenido, esperado, "texto {texto}");
+    }
+}
+
+#[test]
+fn coma_decimal_y_puntos_de_miles() {
+    let coma = SeparadorDecimal::Coma;
+    let normalizar = |texto| coma.normalizar(texto).map(Cow::into_owned);
+    assert_eq!(normalizar("12,5").as_deref(), Some("12.5"));
+    assert_eq!(normalizar("-33,7").as_deref(), Some("-33.7"));
+    assert_eq!(normalizar("1.234").as_deref(), Some("1234"));
+    assert_eq!(normalizar("1.234.567,25").as_deref(), Some("1234567.25"));
+    assert_eq!(normalizar(",5").as_deref(), Some(".5"));
+    assert_eq!(normalizar("1,5e3").as_deref(), Some("1.5e3"));
+    assert_eq!(normalizar("300000").as_deref(), Some("300000"));
+    assert_eq!(normalizar("12.5"), None);
+    assert_eq!(normalizar("1.23"), None);
+    assert_eq!(normalizar("1234.567"), None);
+    assert_eq!(normalizar("1,2,3"), None);
+    assert_eq!(normalizar("12,"), None);
+    assert_eq!(normalizar(""), None);
+    assert_eq!(normalizar("inf"), None);
+    assert_eq!(
+        SeparadorDecimal::Punto.normalizar("12.5").as_deref(),
+        Some("12.5")
+    );
+}
+
+#[test]
+fn localiza_la_salida() {
+    let total = format!("{:.13}", 12.5);
+    assert_eq!(
+        SeparadorDecimal::Coma.localizar(total.clone()),
+        "12,5000000000000"
+    );
+    assert_eq!(SeparadorDecimal::Punto.localizar(total), "12.5000000000000");
+}
+
+#[test]
+fn formatea_con_decimales_y_separador() {
+    assert_eq!(
+        FormatoDeNumeros::default().formatear(1.5),
+        "1.5000000000000"
+    );
+    let formato = FormatoDeNumeros {
+        decimales: 2,
+        separador: SeparadorDecimal::Coma,
+    };
+    assert_eq!(formato.formatear(1234.567), "1234,57");
+}
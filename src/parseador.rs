@@ -1,17 +1,15 @@
 /// Archivo para parsear las entradas del stdin
 use crate::flatlander::Flatlander;
+use crate::geometria::angulo_de_sol_valido;
+use crate::lector::IteradorDeFlatlanders;
+use crate::mensajes::{Idioma, mensaje_de_error};
+use crate::numeros::{SeparadorDecimal, parsear_f64_rapido};
 use std::{
+    fmt,
     io::{self},
     str::FromStr,
 };
 
-/// Constantes definidas para los errores
-const ERR_IO: &str = "\"IO\"";
-const ERR_LINEA_FALTANTE: &str = "\"Linea faltante\"";
-const ERR_VALOR_FALTANTE: &str = "\"Valor faltante\"";
-const ERR_NUMERO_INVALIDO: &str = "\"Numero invalido\"";
-const ERR_FUERA_DE_RANGO: &str = "\"Fuera de rango\"";
-
 /// Parametros definidos por la consigna
 const N_MIN: usize = 1;
 const N_MAX: usize = 100_000;
@@ -19,252 +17,963 @@ const X_MIN: f64 = 0.0;
 const X_MAX: f64 = 300_000.0;
 const H_MIN: f64 = 1.0;
 const H_MAX: f64 = 1000.0;
+const ANGULO_MIN: f64 = 10.0;
+const ANGULO_MAX: f64 = 80.0;
+
+/// Cotas que se validan al parsear
+///
+/// Por defecto son las de la consigna, pero se pueden cambiar para datos reales
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limites {
+    /// Cantidad minima de flatlanders
+    pub n_min: usize,
+    /// Cantidad maxima de flatlanders
+    pub n_max: usize,
+    /// Posicion minima
+    pub x_min: f64,
+    /// Posicion maxima
+    pub x_max: f64,
+    /// Altura minima
+    pub h_min: f64,
+    /// Altura maxima
+    pub h_max: f64,
+    /// Angulo minimo en grados
+    pub angulo_min: f64,
+    /// Angulo maximo en grados
+    pub angulo_max: f64,
+}
+
+impl Limites {
+    /// Los limites de la consigna pero con el sol de cualquier lado, angulos de 0 a 180
+    ///
+    /// Igual se rechazan 0 y 180 porque darian sombras infinitas, con ErrorParseo::AnguloDegenerado
+    pub fn con_sol_de_ambos_lados() -> Self {
+        Self {
+            angulo_min: 0.0,
+            angulo_max: 180.0,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for Limites {
+    fn default() -> Self {
+        Self {
+            n_min: N_MIN,
+            n_max: N_MAX,
+            x_min: X_MIN,
+            x_max: X_MAX,
+            h_min: H_MIN,
+            h_max: H_MAX,
+            angulo_min: ANGULO_MIN,
+            angulo_max: ANGULO_MAX,
+        }
+    }
+}
+
+/// Que tan exigente es el parseo con lo que sobra o falta en la entrada
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModoDeParseo {
+    /// Como siempre: se ignora lo que venga despues del flatlander n y los tokens de mas en cada linea
+    #[default]
+    Normal,
+    /// Es error que haya lineas con datos despues del flatlander n o tokens de mas en una linea
+    Estricto,
+    /// Se saltean las lineas en blanco, se saca el BOM, los bytes que no son UTF-8 se reemplazan
+    /// y n se deduce de las lineas que hay, avisando si no coincide con el encabezado
+    Permisivo,
+}
+
+/// Opciones de como se parsea, aparte de los limites
+///
+/// Por defecto se parsea igual que siempre
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OpcionesDeParseo {
+    /// Usa el parseo de numeros que mira directo los bytes, da los mismos bits que str::parse
+    pub numeros_rapidos: bool,
+    /// Que hacer con lo que sobra o falta
+    pub modo: ModoDeParseo,
+    /// Separador decimal de los numeros, con coma el punto pasa a ser de miles
+    pub decimal: SeparadorDecimal,
+}
+
+/// Avisos que no cortan el parseo, solo en modo permisivo
+#[derive(Debug, Clone, PartialEq)]
+pub enum Advertencia {
+    /// El encabezado decia una cantidad de flatlanders y habia otra
+    CantidadDistinta {
+        /// La n del encabezado
+        declarada: usize,
+        /// Los flatlanders que realmente habia
+        encontrada: usize,
+    },
+}
+
+impl fmt::Display for Advertencia {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Advertencia::CantidadDistinta {
+                declarada,
+                encontrada,
+            } => write!(
+                f,
+                "el encabezado dice {declarada} flatlanders pero hay {encontrada}"
+            ),
+        }
+    }
+}
+
+/// Errores que puede devolver el parseo
+///
+/// Las lineas y columnas empiezan en 1, la columna es la del primer caracter del token
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorParseo {
+    /// No se pudo leer la entrada
+    Io {
+        /// Mensaje del error de la biblioteca estandar
+        mensaje: String,
+    },
+    /// Se esperaba una linea y no habia
+    LineaFaltante {
+        /// Linea que faltaba
+        linea: usize,
+    },
+    /// La linea tenia menos valores de los esperados
+    ValorFaltante {
+        /// Linea con el valor faltante
+        linea: usize,
+        /// Columna donde se esperaba el valor, despues del final de la linea
+        columna: usize,
+    },
+    /// El token no se pudo convertir a numero
+    NumeroInvalido {
+        /// Linea del token
+        linea: usize,
+        /// Columna del token
+        columna: usize,
+        /// Texto del token
+        token: String,
+    },
+    /// El numero se parseo pero no esta en el rango permitido
+    FueraDeRango {
+        /// Linea del token
+        linea: usize,
+        /// Columna del token
+        columna: usize,
+        /// Texto del token
+        token: String,
+        /// Cota inferior del rango violado
+        minimo: f64,
+        /// Cota superior del rango violado
+        maximo: f64,
+    },
+    /// Sobraban tokens en la linea o lineas despues del ultimo flatlander, solo en modo estricto
+    DatosSobrantes {
+        /// Linea del primer dato que sobra
+        linea: usize,
+        /// Columna del primer dato que sobra
+        columna: usize,
+        /// Texto del primer dato que sobra
+        token: String,
+    },
+    /// La directiva del formato anotado no existe, esta repetida o no tiene valor
+    DirectivaInvalida {
+        /// Linea de la directiva
+        linea: usize,
+        /// Columna de la directiva
+        columna: usize,
+        /// Texto de la directiva
+        token: String,
+    },
+    /// Al formato anotado le falta una directiva obligatoria antes del primer flatlander
+    DirectivaFaltante {
+        /// Linea donde ya se tendria que haber dado la directiva
+        linea: usize,
+        /// Clave de la directiva que falta
+        clave: String,
+    },
+    /// La entrada CSV o JSON no respeta la sintaxis del formato
+    FormatoInvalido {
+        /// Linea del error
+        linea: usize,
+        /// Columna del error
+        columna: usize,
+        /// Texto donde se encontro el error, vacio si la entrada se termino antes de tiempo
+        token: String,
+    },
+    /// El angulo esta en los limites pero da una sombra infinita, con 0 o 180 grados
+    AnguloDegenerado {
+        /// Linea del token
+        linea: usize,
+        /// Columna del token
+        columna: usize,
+        /// Texto del token
+        token: String,
+    },
+}
+
+/// Se muestra igual que los mensajes de siempre: el texto en espanol del catalogo entre comillas
+impl fmt::Display for ErrorParseo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", mensaje_de_error(self, Idioma::Espanol))
+    }
+}
+
+impl std::error::Error for ErrorParseo {}
+
+/// Grupos de errores de parseo, para que quien los reciba pueda reaccionar distinto a cada uno
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CategoriaDeError {
+    /// No se pudo leer la entrada
+    Io,
+    /// Faltan o sobran lineas o valores
+    Estructura,
+    /// Un valor no es un numero
+    Numero,
+    /// Un numero esta fuera de los limites
+    Rango,
+    /// La entrada no respeta la sintaxis del formato anotado, el CSV o el JSON
+    Formato,
+}
+
+impl ErrorParseo {
+    /// Categoria a la que pertenece el error
+    pub fn categoria(&self) -> CategoriaDeError {
+        match self {
+            ErrorParseo::Io { .. } => CategoriaDeError::Io,
+            ErrorParseo::LineaFaltante { .. }
+            | ErrorParseo::ValorFaltante { .. }
+            | ErrorParseo::DatosSobrantes { .. } => CategoriaDeError::Estructura,
+            ErrorParseo::NumeroInvalido { .. } => CategoriaDeError::Numero,
+            ErrorParseo::FueraDeRango { .. } | ErrorParseo::AnguloDegenerado { .. } => {
+                CategoriaDeError::Rango
+            }
+            ErrorParseo::DirectivaInvalida { .. }
+            | ErrorParseo::DirectivaFaltante { .. }
+            | ErrorParseo::FormatoInvalido { .. } => CategoriaDeError::Formato,
+        }
+    }
+
+    /// Codigo estable del error, para reconocerlo sin depender del texto ni del idioma
+    ///
+    /// Los codigos no cambian nunca, si se agrega un error nuevo lleva el siguiente numero
+    pub fn codigo(&self) -> &'static str {
+        match self {
+            ErrorParseo::Io { .. } => "E001",
+            ErrorParseo::LineaFaltante { .. } => "E002",
+            ErrorParseo::ValorFaltante { .. } => "E003",
+            ErrorParseo::NumeroInvalido { .. } => "E004",
+            ErrorParseo::FueraDeRango { .. } => "E005",
+            ErrorParseo::DatosSobrantes { .. } => "E006",
+            ErrorParseo::DirectivaInvalida { .. } => "E007",
+            ErrorParseo::DirectivaFaltante { .. } => "E008",
+            ErrorParseo::FormatoInvalido { .. } => "E009",
+            ErrorParseo::AnguloDegenerado { .. } => "E010",
+        }
+    }
+
+    /// Linea donde esta el error, los de IO no tienen
+    pub fn linea(&self) -> Option<usize> {
+        match self {
+            ErrorParseo::Io { .. } => None,
+            ErrorParseo::LineaFaltante { linea }
+            | ErrorParseo::ValorFaltante { linea, .. }
+            | ErrorParseo::NumeroInvalido { linea, .. }
+            | ErrorParseo::FueraDeRango { linea, .. }
+            | ErrorParseo::DatosSobrantes { linea, .. }
+            | ErrorParseo::DirectivaInvalida { linea, .. }
+            | ErrorParseo::DirectivaFaltante { linea, .. }
+            | ErrorParseo::FormatoInvalido { linea, .. }
+            | ErrorParseo::AnguloDegenerado { linea, .. } => Some(*linea),
+        }
+    }
+
+    /// Columna donde esta el error, contada en caracteres desde 1
+    ///
+    /// Los errores que son de toda una linea, o de ninguna, no tienen
+    pub fn columna(&self) -> Option<usize> {
+        match self {
+            ErrorParseo::Io { .. }
+            | ErrorParseo::LineaFaltante { .. }
+            | ErrorParseo::DirectivaFaltante { .. } => None,
+            ErrorParseo::ValorFaltante { columna, .. }
+            | ErrorParseo::NumeroInvalido { columna, .. }
+            | ErrorParseo::FueraDeRango { columna, .. }
+            | ErrorParseo::DatosSobrantes { columna, .. }
+            | ErrorParseo::DirectivaInvalida { columna, .. }
+            | ErrorParseo::FormatoInvalido { columna, .. }
+            | ErrorParseo::AnguloDegenerado { columna, .. } => Some(*columna),
+        }
+    }
+
+    /// Texto que causo el error: el token, la clave de la directiva que falta o el mensaje de IO
+    pub fn texto(&self) -> Option<&str> {
+        match self {
+            ErrorParseo::LineaFaltante { .. } | ErrorParseo::ValorFaltante { .. } => None,
+            ErrorParseo::Io { mensaje } => Some(mensaje),
+            ErrorParseo::DirectivaFaltante { clave, .. } => Some(clave),
+            ErrorParseo::NumeroInvalido { token, .. }
+            | ErrorParseo::FueraDeRango { token, .. }
+            | ErrorParseo::DatosSobrantes { token, .. }
+            | ErrorParseo::DirectivaInvalida { token, .. }
+            | ErrorParseo::FormatoInvalido { token, .. }
+            | ErrorParseo::AnguloDegenerado { token, .. } => Some(token),
+        }
+    }
+}
+
+impl CategoriaDeError {
+    /// Nombre corto de la categoria, el mismo que se usa en la salida de diagnosticos
+    pub fn nombre(self) -> &'static str {
+        match self {
+            CategoriaDeError::Io => "io",
+            CategoriaDeError::Estructura => "estructura",
+            CategoriaDeError::Numero => "numero",
+            CategoriaDeError::Rango => "rango",
+            CategoriaDeError::Formato => "formato",
+        }
+    }
+}
+
+/// Token de una linea junto con su posicion, para poder armar los errores
+pub(crate) struct Token<'a> {
+    pub(crate) texto: &'a str,
+    pub(crate) linea: usize,
+    pub(crate) columna: usize,
+}
+
+/// Separa una linea en tokens recordando la columna de cada uno
+///
+/// Es como split_whitespace pero sin perder la posicion
+pub(crate) struct Tokens<'a> {
+    linea_str: &'a str,
+    split: std::str::SplitWhitespace<'a>,
+    linea: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub(crate) fn nuevo(linea_str: &'a str, linea: usize) -> Self {
+        Self {
+            linea_str,
+            split: linea_str.split_whitespace(),
+            linea,
+        }
+    }
+
+    /// Devuelve el siguiente token o el error de valor faltante
+    pub(crate) fn siguiente(&mut self) -> Result<Token<'a>, ErrorParseo> {
+        match self.split.next() {
+            Some(texto) => Ok(self.token(texto)),
+            None => Err(ErrorParseo::ValorFaltante {
+                linea: self.linea,
+                columna: self.linea_str.chars().count() + 1,
+            }),
+        }
+    }
+
+    /// Devuelve el error de datos sobrantes si todavia quedan tokens en la linea
+    pub(crate) fn verificar_que_no_sobre(&mut self) -> Result<(), ErrorParseo> {
+        match self.split.next() {
+            Some(texto) => {
+                let token = self.token(texto);
+                Err(ErrorParseo::DatosSobrantes {
+                    linea: token.linea,
+                    columna: token.columna,
+                    token: token.texto.to_string(),
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Devuelve el siguiente token, o None si la linea ya se termino
+    pub(crate) fn siguiente_opcional(&mut self) -> Option<Token<'a>> {
+        self.split.next().map(|texto| self.token(texto))
+    }
+
+    /// Arma el token calculando su columna
+    pub(crate) fn token(&self, texto: &'a str) -> Token<'a> {
+        // el token es un pedazo de la linea, asi que la resta de punteros da el desplazamiento
+        let desplazamiento = texto.as_ptr() as usize - self.linea_str.as_ptr() as usize;
+        Token {
+            texto,
+            linea: self.linea,
+            columna: self.linea_str[..desplazamiento].chars().count() + 1,
+        }
+    }
+}
+
+/// Devuelve el error de datos sobrantes para una linea que no tendria que estar, o Ok si esta en blanco
+pub(crate) fn verificar_linea_sobrante(
+    linea: &str,
+    numero_linea: usize,
+) -> Result<(), ErrorParseo> {
+    Tokens::nuevo(linea, numero_linea).verificar_que_no_sobre()
+}
 
 /// Funcion para poder usar en los tests
 ///
 /// Se le dice de donde se lee la entrada
-pub fn leer_entrada_desde(mut entrada: impl std::io::Read) -> Option<String> {
+pub fn leer_entrada_desde(mut entrada: impl std::io::Read) -> Result<String, ErrorParseo> {
     let mut s = String::new();
-    if entrada.read_to_string(&mut s).is_err() {
-        eprintln!("Error: {ERR_IO}");
-        return None;
-    }
-    Some(s)
+    entrada
+        .read_to_string(&mut s)
+        .map_err(|e| ErrorParseo::Io {
+            mensaje: e.to_string(),
+        })?;
+    Ok(s)
 }
 
 /// Lee toda la entrada como un string
 ///
-/// Si hay un error devuelve None
-///
 /// Usa la funcion de arriba y se dice que se lea desde el stdin
-pub fn leer_entrada() -> Option<String> {
+pub fn leer_entrada() -> Result<String, ErrorParseo> {
     leer_entrada_desde(io::stdin())
 }
 
-/// Funcion privada para validar las entradas y no pasarme de 30 lineas en los parseos y borrar codigo repetido
-fn validar_str<'a>(lineas_split: &mut std::str::SplitWhitespace<'a>) -> Option<&'a str> {
-    match lineas_split.next() {
-        Some(s) => Some(s),
-        None => {
-            eprintln!("Error: {ERR_VALOR_FALTANTE}");
-            None
-        }
+/// Funcion privada para parsear los tokens y no pasarme de 30 lineas en los parseos y borrar codigo repetido
+///
+/// A veces necesito f64 y a veces usize, por eso uso tipo generico T. Con coma decimal el texto
+/// se normaliza antes, asi n tambien puede tener puntos de miles
+fn parsear_str_a_numero<T: FromStr>(
+    token: &Token<'_>,
+    decimal: SeparadorDecimal,
+) -> Result<T, ErrorParseo> {
+    decimal
+        .normalizar(token.texto)
+        .and_then(|texto| texto.parse::<T>().ok())
+        .ok_or_else(|| numero_invalido(token))
+}
+
+/// Funcion privada que arma el error de numero invalido con el token
+fn numero_invalido(token: &Token<'_>) -> ErrorParseo {
+    ErrorParseo::NumeroInvalido {
+        linea: token.linea,
+        columna: token.columna,
+        token: token.texto.to_string(),
     }
 }
 
-/// Funcion privada para parsear los str y no pasarme de 30 lineas en los y borrar codigo repetido
-///
-/// A veces necesito f64 y a veces usize, por eso uso tipo generico T
-fn parsear_str_a_numero<T: FromStr>(numero_str: &str) -> Option<T> {
-    match numero_str.parse::<T>() {
-        Ok(v) => Some(v),
-        Err(_) => {
-            eprintln!("Error: {ERR_NUMERO_INVALIDO}");
-            None
-        }
+/// Funcion privada para parsear los f64, por el camino rapido si las opciones lo piden
+pub(crate) fn parsear_token_f64(
+    token: &Token<'_>,
+    opciones: &OpcionesDeParseo,
+) -> Result<f64, ErrorParseo> {
+    if !opciones.numeros_rapidos {
+        return parsear_str_a_numero(token, opciones.decimal);
     }
+    opciones
+        .decimal
+        .normalizar(token.texto)
+        .and_then(|texto| parsear_f64_rapido(&texto).ok())
+        .ok_or_else(|| numero_invalido(token))
 }
 
-/// Funcion privada para crear el encabezado
+/// Funcion privada para validar que un numero ya parseado este en el rango
 ///
-/// Se valida tambien que el angulo este entre 10 y 80
+/// Recibe f64 para que sirva tanto para el angulo como para n, x y h
+pub(crate) fn validar_rango(
+    valor: f64,
+    minimo: f64,
+    maximo: f64,
+    token: &Token<'_>,
+) -> Result<(), ErrorParseo> {
+    if (minimo..=maximo).contains(&valor) {
+        Ok(())
+    } else {
+        Err(ErrorParseo::FueraDeRango {
+            linea: token.linea,
+            columna: token.columna,
+            token: token.texto.to_string(),
+            minimo,
+            maximo,
+        })
+    }
+}
+
+/// Valida la cantidad de flatlanders de los formatos que no tienen n en el encabezado
 ///
-/// Imprime el error en caso de ser necesario
-fn crear_encabezado(lineas_split: &mut std::str::SplitWhitespace<'_>) -> Option<(f64, usize)> {
-    let ang_str = validar_str(lineas_split)?;
-    let n_str = validar_str(lineas_split)?;
-    let angulo: f64 = parsear_str_a_numero(ang_str)?;
-    if !(10.0..=80.0).contains(&angulo) {
-        eprintln!("Error: {ERR_FUERA_DE_RANGO}");
-        return None;
+/// Como no hay token, el error se marca en la posicion que se pase con la cantidad como texto
+pub(crate) fn validar_cantidad(
+    cantidad: usize,
+    limites: &Limites,
+    linea: usize,
+    columna: usize,
+) -> Result<(), ErrorParseo> {
+    let texto = cantidad.to_string();
+    let token = Token {
+        texto: &texto,
+        linea,
+        columna,
+    };
+    validar_rango(
+        cantidad as f64,
+        limites.n_min as f64,
+        limites.n_max as f64,
+        &token,
+    )
+}
+
+/// Parsea el angulo y valida que este en los limites y que de una sombra finita
+pub(crate) fn parsear_angulo(
+    token: &Token<'_>,
+    limites: &Limites,
+    opciones: &OpcionesDeParseo,
+) -> Result<f64, ErrorParseo> {
+    let angulo = parsear_token_f64(token, opciones)?;
+    validar_rango(angulo, limites.angulo_min, limites.angulo_max, token)?;
+    if !angulo_de_sol_valido(angulo) {
+        // aunque los limites lo permitan, una sombra infinita no tiene sentido
+        return Err(ErrorParseo::AnguloDegenerado {
+            linea: token.linea,
+            columna: token.columna,
+            token: token.texto.to_string(),
+        });
     }
-    let n: usize = parsear_str_a_numero(n_str)?;
-    if !(N_MIN..=N_MAX).contains(&n) {
-        eprintln!("Error: {ERR_FUERA_DE_RANGO}");
-        return None;
+    Ok(angulo)
+}
+
+/// Funcion privada para crear el encabezado
+///
+/// Se valida tambien que el angulo y n esten dentro de los limites
+fn crear_encabezado(
+    tokens: &mut Tokens<'_>,
+    limites: &Limites,
+    opciones: &OpcionesDeParseo,
+) -> Result<(f64, usize), ErrorParseo> {
+    let ang_token = tokens.siguiente()?;
+    let n_token = tokens.siguiente()?;
+    let angulo = parsear_angulo(&ang_token, limites, opciones)?;
+    let n: usize = parsear_str_a_numero(&n_token, opciones.decimal)?;
+    validar_rango(
+        n as f64,
+        limites.n_min as f64,
+        limites.n_max as f64,
+        &n_token,
+    )?;
+    if opciones.modo == ModoDeParseo::Estricto {
+        tokens.verificar_que_no_sobre()?;
     }
-    Some((angulo, n))
+    Ok((angulo, n))
 }
 
 /// Parsea el encabezado usando la funcion privada
-pub fn parsear_encabezado(linea_opt: Option<&str>) -> Option<(f64, usize)> {
-    let linea = match linea_opt {
-        Some(l) => l,
-        None => {
-            eprintln!("Error: {ERR_LINEA_FALTANTE}");
-            return None;
-        }
-    };
-    let mut lineas_split = linea.split_whitespace();
+///
+/// El encabezado es siempre la linea 1, si no se pasan limites se usan los de la consigna
+pub fn parsear_encabezado(
+    linea_opt: Option<&str>,
+    limites: Option<&Limites>,
+) -> Result<(f64, usize), ErrorParseo> {
+    let linea = linea_opt.ok_or(ErrorParseo::LineaFaltante { linea: 1 })?;
+    parsear_linea_de_encabezado(
+        linea,
+        1,
+        &limites.copied().unwrap_or_default(),
+        &OpcionesDeParseo::default(),
+    )
+}
 
-    let encabezado = crear_encabezado(&mut lineas_split)?; // me lo hace poner clippy, habia usado un match
+/// Parsea una linea de encabezado sabiendo en que linea esta, para los otros parseadores del crate
+pub(crate) fn parsear_linea_de_encabezado(
+    linea: &str,
+    numero_linea: usize,
+    limites: &Limites,
+    opciones: &OpcionesDeParseo,
+) -> Result<(f64, usize), ErrorParseo> {
+    let mut tokens = Tokens::nuevo(linea, numero_linea);
+    crear_encabezado(&mut tokens, limites, opciones)
+}
 
-    Some(encabezado)
+/// Parsea una linea con un flatlander sabiendo en que linea esta, para los otros parseadores del crate
+pub(crate) fn parsear_linea_de_flatlander(
+    linea: &str,
+    numero_linea: usize,
+    limites: &Limites,
+    opciones: &OpcionesDeParseo,
+) -> Result<Flatlander, ErrorParseo> {
+    let mut tokens = Tokens::nuevo(linea, numero_linea);
+    crear_flatlander(&mut tokens, limites, opciones)
 }
 
-/// Funcion privada para crear el flatlander
+/// Crea el flatlander con los dos primeros tokens, y en modo estricto revisa que no sobre nada
 ///
-/// Imprime el error en caso de ser necesario
-fn crear_flatlander(lineas_split: &mut std::str::SplitWhitespace<'_>) -> Option<Flatlander> {
-    let x_str = validar_str(lineas_split)?;
-    let h_str = validar_str(lineas_split)?;
-    let x: f64 = parsear_str_a_numero(x_str)?;
-    let h: f64 = parsear_str_a_numero(h_str)?;
-    if !(X_MIN..=X_MAX).contains(&x) || !(H_MIN..=H_MAX).contains(&h) {
-        eprintln!("Error: {ERR_FUERA_DE_RANGO}");
-        return None;
+/// Los rangos se validan igual sea cual sea el camino por el que se parsearon los numeros
+pub(crate) fn crear_flatlander(
+    tokens: &mut Tokens<'_>,
+    limites: &Limites,
+    opciones: &OpcionesDeParseo,
+) -> Result<Flatlander, ErrorParseo> {
+    let x_token = tokens.siguiente()?;
+    let h_token = tokens.siguiente()?;
+    let x = parsear_token_f64(&x_token, opciones)?;
+    let h = parsear_token_f64(&h_token, opciones)?;
+    validar_rango(x, limites.x_min, limites.x_max, &x_token)?;
+    validar_rango(h, limites.h_min, limites.h_max, &h_token)?;
+    if opciones.modo == ModoDeParseo::Estricto {
+        tokens.verificar_que_no_sobre()?;
     }
-    Some(Flatlander { x, h })
+    Ok(Flatlander::nuevo(x, h))
 }
 
 /// Parsea los flatlanders usando la funcion privada
-pub fn parsear_flatlanders(lineas: &mut std::str::Lines<'_>, n: usize) -> Option<Vec<Flatlander>> {
-    let mut flatlanders = Vec::with_capacity(n);
-
-    for _ in 0..n {
-        let linea = match lineas.next() {
-            Some(l) => l,
-            None => {
-                eprintln!("Error: {ERR_LINEA_FALTANTE}");
-                return None;
-            }
-        };
-
-        let mut lineas_split = linea.split_whitespace();
-
-        let flatlander = crear_flatlander(&mut lineas_split)?; // lo mismo que en la linea 97
-        flatlanders.push(flatlander);
+///
+/// Se asume que el encabezado ya se leyo, asi que el primer flatlander esta en la linea 2
+///
+/// Si no se pasan limites se usan los de la consigna
+pub fn parsear_flatlanders(
+    lineas: &mut std::str::Lines<'_>,
+    n: usize,
+    limites: Option<&Limites>,
+) -> Result<Vec<Flatlander>, ErrorParseo> {
+    // se reserva de una como antes, pero sin pasar de n_max por si n no se valido
+    let n_max = limites.map_or(N_MAX, |limites| limites.n_max);
+    let mut flatlanders = Vec::with_capacity(n.min(n_max));
+    for flatlander in IteradorDeFlatlanders::nuevo(lineas, n, limites) {
+        flatlanders.push(flatlander?);
     }
-    Some(flatlanders)
+    Ok(flatlanders)
 }
 
 #[test]
 fn parsear_encabezado_ok() {
     let linea = "45 3";
-    let salida = parsear_encabezado(Some(linea));
-    assert_eq!(salida, Some((45.0, 3)));
+    let salida = parsear_encabezado(Some(linea), None);
+    assert_eq!(salida, Ok((45.0, 3)));
 }
 
 #[test]
 fn parsear_encabezado_linea_faltante() {
-    let salida = parsear_encabezado(None);
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(None, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::LineaFaltante { linea: 1 })
+    ));
 }
 
 #[test]
 fn parsear_encabezado_valor_faltante() {
     let linea = "45";
-    let salida = parsear_encabezado(Some(linea));
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(Some(linea), None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::ValorFaltante { linea: 1, .. })
+    ));
 }
 
 #[test]
 fn parsear_encabezado_numero_invalido() {
     let linea = "xx 2";
-    let salida = parsear_encabezado(Some(linea));
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(Some(linea), None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 1,
+            columna: 1,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_encabezado_fuera_de_rango_bajo() {
     let linea = "5 2";
-    let salida = parsear_encabezado(Some(linea));
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(Some(linea), None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 1,
+            columna: 1,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_encabezado_fuera_de_rango_alto() {
     let linea = "85 2";
-    let salida = parsear_encabezado(Some(linea));
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(Some(linea), None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 1,
+            columna: 1,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_ok() {
     let entrada_str = "0 10\n5 20\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 2).unwrap();
+    let salida = parsear_flatlanders(&mut lineas, 2, None).unwrap();
     assert_eq!(salida.len(), 2);
     assert!((salida[0].x - 0.0).abs() < 1e-12 && (salida[0].h - 10.0).abs() < 1e-12);
     assert!((salida[1].x - 5.0).abs() < 1e-12 && (salida[1].h - 20.0).abs() < 1e-12);
 }
 
+#[test]
+fn parsear_flatlanders_reserva_n_de_una() {
+    let entrada_str = "0 10\n5 20\n7 30\n";
+    let mut lineas = entrada_str.lines();
+    let salida = parsear_flatlanders(&mut lineas, 3, None).unwrap();
+    assert!(salida.capacity() >= 3);
+    let mut lineas = entrada_str.lines();
+    let limites = Limites {
+        n_max: 2,
+        ..Limites::default()
+    };
+    // con un n enorme la reserva queda en n_max y el error sale igual
+    assert_eq!(
+        parsear_flatlanders(&mut lineas, usize::MAX, Some(&limites)),
+        Err(ErrorParseo::LineaFaltante { linea: 5 })
+    );
+}
+
 #[test]
 fn parsear_flatlanders_linea_faltante() {
     let entrada_str = "0 10\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 2);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 2, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::LineaFaltante { linea: 3 })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_valor_faltante() {
     let entrada_str = "0\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 1);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 1, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::ValorFaltante { linea: 2, .. })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_numero_invalido_en_x() {
     let entrada_str = "a 10\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 1);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 1, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 2,
+            columna: 1,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_numero_invalido_en_h() {
     let entrada_str = "0 a\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 1);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 1, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 2,
+            columna: 3,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_encabezado_n_fuera_de_rango_bajo() {
     let linea = "45 0";
-    let salida = parsear_encabezado(Some(linea));
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(Some(linea), None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 1,
+            columna: 4,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_encabezado_n_fuera_de_rango_alto() {
     let linea = "45 100001";
-    let salida = parsear_encabezado(Some(linea));
-    assert!(salida.is_none());
+    let salida = parsear_encabezado(Some(linea), None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 1,
+            columna: 4,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_x_fuera_de_rango_alto() {
     let entrada_str = "300001 10\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 1);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 1, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 2,
+            columna: 1,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_h_fuera_de_rango_bajo() {
     let entrada_str = "0 0\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 1);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 1, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 2,
+            columna: 3,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_h_fuera_de_rango_alto() {
     let entrada_str = "0 1001\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 1);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, 1, None);
+    assert!(matches!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 2,
+            columna: 3,
+            ..
+        })
+    ));
 }
 
 #[test]
 fn parsear_flatlanders_espacios_y_linea_en_blanco() {
     let entrada_str = "   0    10   \n5    20   \n\n";
     let mut lineas = entrada_str.lines();
-    let salida = parsear_flatlanders(&mut lineas, 2).unwrap();
+    let salida = parsear_flatlanders(&mut lineas, 2, None).unwrap();
     assert_eq!(salida.len(), 2);
     assert!((salida[0].x - 0.0).abs() < 1e-12 && (salida[0].h - 10.0).abs() < 1e-12);
     assert!((salida[1].x - 5.0).abs() < 1e-12 && (salida[1].h - 20.0).abs() < 1e-12);
 }
+
+#[test]
+fn error_numero_invalido_tiene_linea_columna_y_token() {
+    let entrada_str = "0 10\n  5   abc\n";
+    let mut lineas = entrada_str.lines();
+    let salida = parsear_flatlanders(&mut lineas, 2, None);
+    assert_eq!(
+        salida,
+        Err(ErrorParseo::NumeroInvalido {
+            linea: 3,
+            columna: 7,
+            token: "abc".to_string(),
+        })
+    );
+}
+
+#[test]
+fn error_fuera_de_rango_tiene_la_cota() {
+    let salida = parsear_encabezado(Some("45 100001"), None);
+    assert_eq!(
+        salida,
+        Err(ErrorParseo::FueraDeRango {
+            linea: 1,
+            columna: 4,
+            token: "100001".to_string(),
+            minimo: 1.0,
+            maximo: 100_000.0,
+        })
+    );
+}
+
+#[test]
+fn error_valor_faltante_apunta_al_final_de_la_linea() {
+    let salida = parsear_encabezado(Some("45"), None);
+    assert_eq!(
+        salida,
+        Err(ErrorParseo::ValorFaltante {
+            linea: 1,
+            columna: 3
+        })
+    );
+}
+
+#[test]
+fn error_linea_faltante_tiene_la_linea() {
+    let entrada_str = "0 10\n";
+    let mut lineas = entrada_str.lines();
+    let salida = parsear_flatlanders(&mut lineas, 2, None);
+    assert_eq!(salida, Err(ErrorParseo::LineaFaltante { linea: 3 }));
+}
+
+#[test]
+fn display_mantiene_los_mensajes_de_siempre() {
+    let error = ErrorParseo::LineaFaltante { linea: 1 };
+    assert_eq!(error.to_string(), "\"Linea faltante\"");
+}
+
+#[test]
+fn limites_por_defecto_son_los_de_la_consigna() {
+    let limites = Limites::default();
+    assert_eq!((limites.n_min, limites.n_max), (1, 100_000));
+    assert_eq!((limites.x_min, limites.x_max), (0.0, 300_000.0));
+    assert_eq!((limites.h_min, limites.h_max), (1.0, 1000.0));
+    assert_eq!((limites.angulo_min, limites.angulo_max), (10.0, 80.0));
+}
+
+#[test]
+fn limites_ampliados_aceptan_lo_que_la_consigna_rechaza() {
+    let limites = Limites {
+        n_max: 5_000_000,
+        x_min: -1e9,
+        h_max: 1e6,
+        ..Limites::default()
+    };
+    assert_eq!(
+        parsear_encabezado(Some("45 2000000"), Some(&limites)),
+        Ok((45.0, 2_000_000))
+    );
+    let entrada_str = "-250 5000\n";
+    let mut lineas = entrada_str.lines();
+    let salida = parsear_flatlanders(&mut lineas, 1, Some(&limites)).unwrap();
+    assert_eq!(salida[0], Flatlander::nuevo(-250.0, 5000.0));
+}
+
+#[test]
+fn sol_de_ambos_lados_acepta_angulos_pasando_90() {
+    let limites = Limites::con_sol_de_ambos_lados();
+    assert_eq!(
+        parsear_encabezado(Some("135 2"), Some(&limites)),
+        Ok((135.0, 2))
+    );
+    assert!(matches!(
+        parsear_encabezado(Some("135 2"), None),
+        Err(ErrorParseo::FueraDeRango { token, .. }) if token == "135"
+    ));
+}
+
+#[test]
+fn sol_de_ambos_lados_rechaza_0_y_180() {
+    let limites = Limites::con_sol_de_ambos_lados();
+    assert_eq!(
+        parsear_encabezado(Some("0 2"), Some(&limites)),
+        Err(ErrorParseo::AnguloDegenerado {
+            linea: 1,
+            columna: 1,
+            token: "0".to_string(),
+        })
+    );
+    assert!(matches!(
+        parsear_encabezado(Some("180 2"), Some(&limites)),
+        Err(ErrorParseo::AnguloDegenerado { .. })
+    ));
+}
@@ -0,0 +1,55 @@
+/// Archivo con lo que comparten los tests de varios archivos, solo se compila para los tests
+use crate::flatlander::Flatlander;
+
+/// Generador congruencial de 64 bits, para tener datos pseudoaleatorios sin depender de crates
+pub(crate) struct Generador {
+    estado: u64,
+}
+
+impl Generador {
+    /// Crea el generador, con la misma semilla da siempre la misma secuencia
+    pub(crate) fn nuevo(semilla: u64) -> Self {
+        Self { estado: semilla }
+    }
+
+    /// Avanza el generador y devuelve el estado nuevo
+    pub(crate) fn siguiente(&mut self) -> u64 {
+        self.estado = self
+            .estado
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        self.estado
+    }
+
+    /// Devuelve un numero entre 0 y 1, sin incluir el 1, con los 53 bits de arriba del estado
+    pub(crate) fn siguiente_f64(&mut self) -> f64 {
+        (self.siguiente() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Los tres flatlanders del ejemplo de la consigna, desordenados por x
+pub(crate) fn flatlanders_del_ejemplo() -> Vec<Flatlander> {
+    vec![
+        Flatlander::nuevo(50.0, 150.0),
+        Flatlander::nuevo(0.0, 100.0),
+        Flatlander::nuevo(100.0, 200.0),
+    ]
+}
+
+/// El ejemplo de la consigna mas flatlanders lejos, bajos y con x repetidas
+pub(crate) fn flatlanders_variados() -> Vec<Flatlander> {
+    let mut flatlanders = flatlanders_del_ejemplo();
+    flatlanders.extend([
+        Flatlander::nuevo(400.0, 3.0),
+        Flatlander::nuevo(420.0, 30.0),
+        Flatlander::nuevo(425.0, 2.0),
+        Flatlander::nuevo(425.0, 1.0),
+        Flatlander::nuevo(900.0, 50.0),
+    ]);
+    flatlanders
+}
+
+/// Dos flatlanders de altura 2, uno en 0 y el otro en x
+pub(crate) fn dos_flatlanders(x: f64) -> Vec<Flatlander> {
+    vec![Flatlander::nuevo(0.0, 2.0), Flatlander::nuevo(x, 2.0)]
+}
@@ -10,8 +10,8 @@ fn err_encabezado_numero_invalido() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let encabezado = parsear_encabezado(lineas.next());
-    assert!(encabezado.is_none());
+    let encabezado = parsear_encabezado(lineas.next(), None);
+    assert!(encabezado.is_err());
 }
 
 #[test]
@@ -21,8 +21,8 @@ fn err_angulo_fuera_de_rango_bajo() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let encabezado = parsear_encabezado(lineas.next());
-    assert!(encabezado.is_none());
+    let encabezado = parsear_encabezado(lineas.next(), None);
+    assert!(encabezado.is_err());
 }
 
 #[test]
@@ -32,8 +32,8 @@ fn err_angulo_fuera_de_rango_alto() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let encabezado = parsear_encabezado(lineas.next());
-    assert!(encabezado.is_none());
+    let encabezado = parsear_encabezado(lineas.next(), None);
+    assert!(encabezado.is_err());
 }
 
 #[test]
@@ -43,8 +43,8 @@ fn err_valor_faltante_en_encabezado() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let encabezado = parsear_encabezado(lineas.next());
-    assert!(encabezado.is_none());
+    let encabezado = parsear_encabezado(lineas.next(), None);
+    assert!(encabezado.is_err());
 }
 
 #[test]
@@ -54,13 +54,13 @@ fn err_linea_faltante_en_datos() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let (_angulo, n) = match parsear_encabezado(lineas.next()) {
-        Some(v) => v,
-        None => return,
+    let (_angulo, n) = match parsear_encabezado(lineas.next(), None) {
+        Ok(v) => v,
+        Err(_) => return,
     };
 
-    let salida = parsear_flatlanders(&mut lineas, n);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, n, None);
+    assert!(salida.is_err());
 }
 
 #[test]
@@ -70,13 +70,13 @@ fn err_numero_invalido_en_x() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let (_angulo, n) = match parsear_encabezado(lineas.next()) {
-        Some(v) => v,
-        None => return,
+    let (_angulo, n) = match parsear_encabezado(lineas.next(), None) {
+        Ok(v) => v,
+        Err(_) => return,
     };
 
-    let salida = parsear_flatlanders(&mut lineas, n);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, n, None);
+    assert!(salida.is_err());
 }
 
 #[test]
@@ -86,11 +86,11 @@ fn err_numero_invalido_en_h() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let (_angulo, n) = match parsear_encabezado(lineas.next()) {
-        Some(v) => v,
-        None => return,
+    let (_angulo, n) = match parsear_encabezado(lineas.next(), None) {
+        Ok(v) => v,
+        Err(_) => return,
     };
 
-    let salida = parsear_flatlanders(&mut lineas, n);
-    assert!(salida.is_none());
+    let salida = parsear_flatlanders(&mut lineas, n, None);
+    assert!(salida.is_err());
 }
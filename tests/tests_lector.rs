@@ -0,0 +1,75 @@
+use ejercicio_individual_1_santiagosielecki::{
+    flatlander::Flatlander,
+    lector::{parsear_escena_con_opciones, parsear_escena_desde},
+    parseador::{
+        ErrorParseo, OpcionesDeParseo, leer_entrada_desde, parsear_encabezado, parsear_flatlanders,
+    },
+};
+
+/// Parsea con el camino de siempre, cargando toda la entrada en un String
+fn parsear_con_string(bytes: &[u8]) -> Result<(f64, Vec<Flatlander>), ErrorParseo> {
+    let entrada = leer_entrada_desde(bytes)?;
+    let mut lineas = entrada.lines();
+    let (angulo, n) = parsear_encabezado(lineas.next(), None)?;
+    let flatlanders = parsear_flatlanders(&mut lineas, n, None)?;
+    Ok((angulo, flatlanders))
+}
+
+/// Se testea que el lector de a una linea devuelva exactamente lo mismo que el camino de siempre
+#[test]
+fn lector_coincide_con_el_parseo_de_siempre() {
+    let entradas = [
+        "45 2\n0 10\n5 10\n",
+        "30 3\n50 150\n0 100\n100 200\n",
+        "   45   2  \n   0    10   \n5    20   \n\n",
+        "45 2\r\n0 10\r\n5 10\r\n",
+        "45 1\n0 10\nsobra\n",
+        "",
+        "xx 2\n0 10\n0 10\n",
+        "5 2\n0 10\n0 10\n",
+        "45\n0 10\n",
+        "45 2\n0 10\n",
+        "45 1\na 10\n",
+        "45 1\n0 a\n",
+        "45 1\n0\n",
+        "45 1\n300001 10\n",
+        "45 100001\n",
+        "33.7 2\n0.1 12.5\n1e2 999.9999999999999\n",
+        "45 1\n-0 1\n",
+        "45 1\n1.2.3 1\n",
+    ];
+    for entrada in entradas {
+        assert_eq!(
+            parsear_escena_desde(entrada.as_bytes(), None),
+            parsear_con_string(entrada.as_bytes()),
+            "entrada {entrada:?}"
+        );
+        let rapidos = OpcionesDeParseo {
+            numeros_rapidos: true,
+            ..OpcionesDeParseo::default()
+        };
+        assert_eq!(
+            parsear_escena_con_opciones(entrada.as_bytes(), None, rapidos),
+            parsear_con_string(entrada.as_bytes()),
+            "entrada {entrada:?} con numeros rapidos"
+        );
+    }
+}
+
+#[test]
+fn lector_rechaza_utf8_invalido_despues_del_ultimo_flatlander() {
+    let entradas: [&[u8]; 3] = [
+        b"45 1\n0 10\n\xff\xfe\n",
+        b"45 1\n0 10\n\n\nbien\nmal \xc3\n",
+        b"45 1\n0 10\n\xe2\x82",
+    ];
+    for entrada in entradas {
+        let resultado = parsear_escena_desde(entrada, None);
+        assert!(matches!(resultado, Err(ErrorParseo::Io { .. })));
+        assert_eq!(
+            resultado,
+            parsear_con_string(entrada),
+            "entrada {entrada:?}"
+        );
+    }
+}
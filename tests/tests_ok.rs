@@ -16,20 +16,20 @@ fn ok_superposicion_simple_45() {
     let entrada_str = "45 2\n0 10\n5 10\n";
     let mut bytes = entrada_str.as_bytes();
     let entrada = match leer_entrada_desde(&mut bytes) {
-        Some(e) => e,
-        None => return,
+        Ok(e) => e,
+        Err(_) => return,
     };
 
     let mut lineas = entrada.lines();
 
-    let (angulo, n) = match parsear_encabezado(lineas.next()) {
-        Some(val) => val,
-        None => return,
+    let (angulo, n) = match parsear_encabezado(lineas.next(), None) {
+        Ok(val) => val,
+        Err(_) => return,
     };
 
-    let flatlanders = match parsear_flatlanders(&mut lineas, n) {
-        Some(v) => v,
-        None => return,
+    let flatlanders = match parsear_flatlanders(&mut lineas, n, None) {
+        Ok(v) => v,
+        Err(_) => return,
     };
 
     let sombras = sombras_desde_flatlanders(&flatlanders, angulo);
@@ -45,8 +45,8 @@ fn ok_ejemplo_30_grados() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let (angulo, n) = parsear_encabezado(lineas.next()).unwrap();
-    let flatlanders = parsear_flatlanders(&mut lineas, n).unwrap();
+    let (angulo, n) = parsear_encabezado(lineas.next(), None).unwrap();
+    let flatlanders = parsear_flatlanders(&mut lineas, n, None).unwrap();
 
     let sombras = sombras_desde_flatlanders(&flatlanders, angulo);
     let total = longitud_union(sombras);
@@ -62,8 +62,8 @@ fn ok_ejemplo_45_grados() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let (angulo, n) = parsear_encabezado(lineas.next()).unwrap();
-    let flatlanders = parsear_flatlanders(&mut lineas, n).unwrap();
+    let (angulo, n) = parsear_encabezado(lineas.next(), None).unwrap();
+    let flatlanders = parsear_flatlanders(&mut lineas, n, None).unwrap();
 
     let sombras = sombras_desde_flatlanders(&flatlanders, angulo);
     let total = longitud_union(sombras);
@@ -79,8 +79,8 @@ fn ok_sin_solapamiento_45() {
     let entrada = leer_entrada_desde(&mut bytes).unwrap();
 
     let mut lineas = entrada.lines();
-    let (angulo, n) = parsear_encabezado(lineas.next()).unwrap();
-    let flatlanders = parsear_flatlanders(&mut lineas, n).unwrap();
+    let (angulo, n) = parsear_encabezado(lineas.next(), None).unwrap();
+    let flatlanders = parsear_flatlanders(&mut lineas, n, None).unwrap();
 
     let sombras = sombras_desde_flatlanders(&flatlanders, angulo);
     let total = longitud_union(sombras);